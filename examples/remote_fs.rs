@@ -0,0 +1,184 @@
+//! 从远程对象存储（这里用内存模拟）读取文件
+//! 远程数据只能按顺序读取，`RemoteAccess`缓存已经读到的数据来支持范围请求
+use futures_util::{
+    future::{ready, Ready},
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use http::{Request, Response};
+use hyper::{body::Bytes, service::service_fn};
+use hyper_staticfile::{
+    vfs::{FileAccess, FileOpener, FileWithMetadata, IntoFileAccess},
+    Body, Static,
+};
+use hyper_util::rt::TokioIo;
+use std::{
+    collections::HashMap,
+    io::{Error as IoError, ErrorKind, SeekFrom},
+    net::SocketAddr,
+    path::Path,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::SystemTime,
+};
+use tokio::{io::AsyncSeek, net::TcpListener};
+
+/// 模拟远程存储每次返回的数据块大小
+const CHUNK_SIZE: usize = 4;
+
+type RemoteBody = BoxStream<'static, Result<Bytes, IoError>>;
+
+/// 模拟的对象存储，对象只能以数据流的形式从头读取
+#[derive(Clone, Default)]
+struct RemoteStore {
+    objects: Arc<HashMap<String, (Bytes, SystemTime)>>,
+}
+
+impl RemoteStore {
+    fn get_object(&self, key: &str) -> Option<(RemoteBody, u64, SystemTime)> {
+        let (data, modified) = self.objects.get(key)?.clone();
+        let size = data.len() as u64;
+        let chunks: Vec<_> = (0..data.len())
+            .step_by(CHUNK_SIZE)
+            .map(|start| Ok(data.slice(start..data.len().min(start + CHUNK_SIZE))))
+            .collect();
+        Some((stream::iter(chunks).boxed(), size, modified))
+    }
+}
+
+/// 远程对象，读取时才转为`RemoteAccess`
+struct RemoteObject {
+    body: RemoteBody,
+}
+
+impl IntoFileAccess for RemoteObject {
+    type Output = RemoteAccess;
+    fn into_file_access(self) -> Self::Output {
+        RemoteAccess {
+            body: self.body,
+            buffer: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+/// 记录读取位置，seek只修改位置，读取时按需从数据流中补齐缓存
+struct RemoteAccess {
+    body: RemoteBody,
+    buffer: Vec<u8>,
+    position: u64,
+}
+
+impl AsyncSeek for RemoteAccess {
+    fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> Result<(), IoError> {
+        self.position = match position {
+            SeekFrom::Start(offset) => offset,
+            SeekFrom::Current(offset) => self
+                .position
+                .checked_add_signed(offset)
+                .ok_or_else(|| IoError::from(ErrorKind::InvalidInput))?,
+            // 数据流的总长度未知，不支持从末尾定位
+            SeekFrom::End(_) => return Err(IoError::from(ErrorKind::Unsupported)),
+        };
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<u64, IoError>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+impl FileAccess for RemoteAccess {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        len: usize,
+    ) -> Poll<Result<Bytes, IoError>> {
+        let this = &mut *self;
+        let start = this.position as usize;
+        while this.buffer.len() <= start {
+            match this.body.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(chunk))) => this.buffer.extend_from_slice(&chunk),
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e)),
+                Poll::Ready(None) => return Poll::Ready(Ok(Bytes::new())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        let end = this.buffer.len().min(start + len);
+        this.position = end as u64;
+        Poll::Ready(Ok(Bytes::copy_from_slice(&this.buffer[start..end])))
+    }
+}
+
+struct RemoteOpener {
+    store: RemoteStore,
+}
+
+impl FileOpener for RemoteOpener {
+    type File = RemoteObject;
+    type Future = Ready<Result<FileWithMetadata<Self::File>, IoError>>;
+
+    fn open(&self, path: &Path) -> Self::Future {
+        let key = path.to_string_lossy();
+        let file = match self.store.get_object(&key) {
+            Some((body, size, modified)) => Ok(FileWithMetadata {
+                handle: RemoteObject { body },
+                size,
+                modified: Some(modified),
+                is_dir: false,
+                unique_id: None,
+            }),
+            None => Err(IoError::from(ErrorKind::NotFound)),
+        };
+        ready(file)
+    }
+}
+
+async fn handle_request<B>(
+    req: Request<B>,
+    static_: Static<RemoteOpener>,
+) -> Result<Response<Body<RemoteAccess>>, IoError> {
+    static_.clone().serve(req).await
+}
+
+#[tokio::main]
+async fn main() {
+    let mut objects = HashMap::new();
+    objects.insert(
+        "hello.txt".to_string(),
+        (
+            Bytes::from_static(b"hello from remote storage\n"),
+            SystemTime::now(),
+        ),
+    );
+    let store = RemoteStore {
+        objects: Arc::new(objects),
+    };
+
+    // curl -H 'Range: bytes=6-9' http://127.0.0.1:3000/hello.txt
+    let static_ = Static::with_opener(RemoteOpener { store });
+    let addr: SocketAddr = ([127, 0, 0, 1], 3000).into();
+    let listener = TcpListener::bind(addr)
+        .await
+        .expect("Failed to create TCP connection");
+
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .expect("Failed to accept TCP Connection");
+        let static_ = static_.clone();
+        tokio::spawn(async move {
+            if let Err(err) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(
+                    TokioIo::new(stream),
+                    service_fn(move |req| handle_request(req, static_.clone())),
+                )
+                .await
+            {
+                eprintln!("Error serving connection: {:?}", err);
+            }
+        });
+    }
+}
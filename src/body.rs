@@ -1,17 +1,38 @@
 use futures_util::Stream;
-use hyper::body::{Bytes, Frame};
-use std::{io::Error as IoError, pin::Pin, task::{ready, Poll}};
+use hyper::body::{Bytes, Frame, SizeHint};
+use std::{io::Error as IoError, mem, pin::Pin, task::{ready, Poll}};
 
+#[cfg(feature = "compression")]
+use crate::util::FileBytesStreamCompressed;
+#[cfg(feature = "digest")]
+use crate::util::FileBytesStreamDigest;
 use crate::{
-    util::{FileBytesStream, FileBytesStreamMultiRange, FileBytesStreamRange},
+    util::{
+        FileBytesStream, FileBytesStreamMultiRange, FileBytesStreamRange, FileBytesStreamWrapped,
+    },
     vfs::{FileAccess, TokioFileAccess},
 };
 
+/// 自定义的字节流
+pub type BoxBytesStream = Pin<Box<dyn Stream<Item = Result<Bytes, IoError>> + Send>>;
+
 pub enum Body<F = TokioFileAccess> {
     Empty,
+    /// 内存中的完整内容
+    Bytes(Bytes),
     Full(FileBytesStream<F>),
     Range(FileBytesStreamRange<F>),
     MultiRange(FileBytesStreamMultiRange<F>),
+    /// 前后追加了固定内容的完整文件
+    Wrapped(FileBytesStreamWrapped<F>),
+    /// 动态压缩的完整文件
+    #[cfg(feature = "compression")]
+    Compressed(FileBytesStreamCompressed<F>),
+    /// 任意来源的字节流，长度未知
+    Stream(BoxBytesStream),
+    /// 完整文件，结束时以trailer输出`Content-Digest`
+    #[cfg(feature = "digest")]
+    Digest(FileBytesStreamDigest<F>),
 }
 
 impl<F: FileAccess> hyper::body::Body for Body<F> {
@@ -23,10 +44,41 @@ impl<F: FileAccess> hyper::body::Body for Body<F> {
     ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
         let opt = ready!(match *self{
             Body::Empty => return Poll::Ready(None),
+            Body::Bytes(ref mut bytes) => {
+                if bytes.is_empty() {
+                    return Poll::Ready(None);
+                }
+                return Poll::Ready(Some(Ok(Frame::data(mem::take(bytes)))));
+            }
             Body::Full(ref mut stream) => Pin::new(stream).poll_next(cx),
             Body::Range(ref mut stream) => Pin::new(stream).poll_next(cx),
             Body::MultiRange(ref mut stream) => Pin::new(stream).poll_next(cx),
+            Body::Wrapped(ref mut stream) => Pin::new(stream).poll_next(cx),
+            Body::Stream(ref mut stream) => stream.as_mut().poll_next(cx),
+            #[cfg(feature = "compression")]
+            Body::Compressed(ref mut stream) => Pin::new(stream).poll_next(cx),
+            #[cfg(feature = "digest")]
+            Body::Digest(ref mut stream) => {
+                return match ready!(Pin::new(&mut *stream).poll_next(cx)) {
+                    Some(res) => Poll::Ready(Some(res.map(Frame::data))),
+                    None => Poll::Ready(stream.take_trailers().map(|t| Ok(Frame::trailers(t)))),
+                };
+            }
         }) ;
         Poll::Ready(opt.map(|res| res.map(Frame::data)))
     }
+
+    fn size_hint(&self) -> SizeHint {
+        let remaining = match *self {
+            Body::Empty => Some(0),
+            Body::Bytes(ref bytes) => Some(bytes.len() as u64),
+            Body::Full(ref stream) => stream.remaining(),
+            Body::Range(ref stream) => stream.remaining(),
+            Body::MultiRange(ref stream) => Some(stream.compute_length()),
+            Body::Wrapped(ref stream) => stream.remaining(),
+            // 压缩后的长度、任意字节流的长度和带trailer的响应体长度都无法预知
+            _ => None,
+        };
+        remaining.map(SizeHint::with_exact).unwrap_or_default()
+    }
 }
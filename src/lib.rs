@@ -5,7 +5,7 @@ mod service;
 
 pub mod util;
 pub mod vfs;
-pub use crate::body::Body;
+pub use crate::body::{Body, BoxBytesStream};
 pub use crate::resolve::*;
 pub use crate::response_builder::*;
 pub use crate::service::*;
\ No newline at end of file
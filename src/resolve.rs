@@ -1,28 +1,59 @@
 /// 解析器，获取到请求路径、获取请求文件元信息、编码等
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
-use std::{ops::BitAnd, path::PathBuf, sync::Arc, time::SystemTime};
+use std::{
+    ops::BitAnd,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use futures_util::future::BoxFuture;
-use http::{header, HeaderValue, Method, Request};
+use futures_util::StreamExt;
+use http::{header, HeaderName, HeaderValue, Method, Request, StatusCode};
 use mime_guess::{mime, Mime, MimeGuess};
+use percent_encoding::percent_encode;
 use std::io::Error as IoError;
 use std::io::ErrorKind as IoErrorKind;
 use std::io::Result as IoResult;
+#[cfg(feature = "digest")]
+use std::sync::Mutex;
 use tokio::fs::File;
 
-use crate::util::RequestedPath;
-use crate::vfs::{FileOpener, MemoryFs};
+#[cfg(feature = "digest")]
+use crate::util::LruCache;
+use crate::util::{FileBytesStream, RequestedPath, PATH_SEGMENT};
+use crate::vfs::{DirectoryEntry, FileOpener, IntoFileAccess, MemoryFs};
 use crate::vfs::{FileWithMetadata, TokioFileOpener};
 
 /// 文件解析结果
 #[derive(Debug)]
 pub struct ResolvedFile<F = File> {
     pub handle: F,
+    /// 实际输出的文件路径，命中预压缩文件时是`app.js.br`这样的路径
     pub path: PathBuf,
+    /// 请求对应的原始文件路径，不受预压缩文件影响，可用于构造缓存键
+    pub logical_path: PathBuf,
+    /// 实际输出文件的大小，命中预压缩文件时为压缩后的大小，直接用作Content-Length
     pub size: u64,
     pub modified: Option<SystemTime>,
+    /// 实际输出文件在文件系统中的唯一标识，参见`FileWithMetadata::unique_id`
+    pub unique_id: Option<u64>,
     pub content_type: Option<String>,
     pub encoding: Option<Encoding>,
+    /// 请求头中的`Priority`，供下游调度使用
+    pub priority: Option<Priority>,
+    /// 不带末尾斜杠的目录请求直接返回index时，指向实际的index路径
+    pub content_location: Option<String>,
+    /// 响应的`Vary`中列出的请求头
+    pub vary: Vec<HeaderName>,
+    /// 由内容生成的强ETag（带引号），设置后代替由大小和修改时间生成的弱ETag
+    pub etag: Option<String>,
+    /// 替换200响应的状态码，如SPA回退返回404，此时忽略条件请求和范围请求
+    pub status: Option<StatusCode>,
+    /// `encoding`不是预压缩文件，需要在输出时动态压缩
+    #[cfg(feature = "compression")]
+    pub compress: bool,
 }
 
 impl<F> ResolvedFile<F> {
@@ -34,28 +65,180 @@ impl<F> ResolvedFile<F> {
     ) -> Self {
         Self {
             handle: file.handle,
+            logical_path: path.clone(),
             path,
             size: file.size,
             modified: file.modified,
+            unique_id: file.unique_id,
             content_type,
             encoding,
+            priority: None,
+            content_location: None,
+            vary: Vec::new(),
+            etag: None,
+            status: None,
+            #[cfg(feature = "compression")]
+            compress: false,
+        }
+    }
+
+    /// 直接由各部分构造，用于不经过`FileOpener`的自定义内容
+    pub fn from_parts(
+        handle: F,
+        path: impl Into<PathBuf>,
+        size: u64,
+        modified: Option<SystemTime>,
+        content_type: Option<String>,
+        encoding: Option<Encoding>,
+    ) -> Self {
+        let path = path.into();
+        Self {
+            handle,
+            logical_path: path.clone(),
+            path,
+            size,
+            modified,
+            unique_id: None,
+            content_type,
+            encoding,
+            priority: None,
+            content_location: None,
+            vary: Vec::new(),
+            etag: None,
+            status: None,
+            #[cfg(feature = "compression")]
+            compress: false,
+        }
+    }
+
+    /// 不含文件句柄的元信息
+    pub fn metadata(&self) -> ResolvedMetadata {
+        ResolvedMetadata {
+            path: self.path.clone(),
+            logical_path: self.logical_path.clone(),
+            size: self.size,
+            modified: self.modified,
+            content_type: self.content_type.clone(),
+            encoding: self.encoding,
+            etag: self.etag.clone(),
         }
     }
 }
 
+/// 文件解析结果中的元信息，可用于自行处理条件请求或生成`Link`预加载头
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedMetadata {
+    pub path: PathBuf,
+    pub logical_path: PathBuf,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub content_type: Option<String>,
+    pub encoding: Option<Encoding>,
+    pub etag: Option<String>,
+}
+
 /// 解析者
 pub struct Resolver<O = TokioFileOpener> {
     /// 打开文件
     pub opener: Arc<O>,
+    /// 允许的请求方法，也用于生成405响应的Allow头，不包含OPTIONS时OPTIONS请求同样返回405
+    /// 只有GET、HEAD和OPTIONS能够处理，其它方法即使在这里也返回405，参见`set_allowed_methods`
+    pub allowed_methods: Vec<Method>,
     /// 允许的编码
     pub allowed_encodings: AcceptEncoding,
     /// 重写解析参数
     pub rewrite: Option<Arc<dyn (Fn(ResolveParams) -> BoxRewriteFuture) + Send + Sync>>,
+    /// 在标准化之前映射原始请求路径，返回None表示NotFound
+    pub path_map: Option<Arc<PathMapFn>>,
+    /// 根据请求的Accept头为指定的请求路径选择Content-Type，文件内容不变
+    pub content_type_variants: HashMap<String, Vec<Mime>>,
+    /// 按扩展名指定MIME类型，优先于`mime_guess`的猜测，扩展名不区分大小写
+    pub mime_overrides: HashMap<String, Mime>,
+    /// 按文件开头的字节识别MIME类型，扩展名无法确定类型时按顺序匹配
+    pub magic_types: Vec<(Vec<u8>, Mime)>,
+    /// 文本类型（`text/*`、JavaScript和JSON）附加的charset，为None时不附加
+    pub charset: Option<String>,
+    /// 无法猜测类型时使用的Content-Type，如`application/octet-stream`，默认为None即不设置
+    pub default_content_type: Option<String>,
+    /// 可压缩的MIME类型，只有这些类型才会动态压缩，比较时忽略参数
+    /// 预压缩文件不受限制，存在时总是参与协商
+    pub compressible_types: HashSet<Mime>,
+    /// 无扩展名的地址对应`.html`文件，`.html`地址重定向到无扩展名的地址
+    pub clean_urls: bool,
+    /// `resolve_request`解析到文件并完成Accept协商后、构建响应前调用，返回Some时替换解析结果
+    /// 直接调用`resolve_path`时不会调用
+    pub on_resolved: Option<Arc<dyn OnResolvedFn<O>>>,
+    /// 末尾斜杠与目标类型不符时的处理方式
+    pub trailing_slash: TrailingSlash,
+    /// 路径片段末尾的`.`和空格的处理方式，拒绝时返回BadRequest
+    pub trailing_dots: TrailingDots,
+    /// 在不区分大小写的文件系统上，请求路径的大小写与磁盘上不一致时重定向到磁盘上的路径
+    /// 需要`opener`支持`read_dir`，每一层路径都要列出一次目录
+    pub canonical_case_redirect: bool,
+    /// 无论Accept-Encoding如何，都优先使用该编码的预压缩文件
+    pub force_encoding: Option<Encoding>,
+    /// 按编码指定查找预压缩文件的位置，未指定的编码在`opener`中查找
+    pub sidecar_openers: HashMap<Encoding, Arc<O>>,
+    /// 目录请求依次尝试的index文件名
+    pub index_files: Vec<String>,
+    /// 目录下没有index时列出目录内容
+    pub autoindex: bool,
+    /// 目录中存在该文件时既不返回index也不列出目录，如`.noindex`
+    pub noindex_marker: Option<String>,
+    /// 请求路径最多允许的层数，更深的请求返回NotFound
+    pub max_depth: Option<usize>,
+    /// 是否允许访问以`.`开头的文件和目录，如`.git`、`.env`
+    pub serve_dotfiles: bool,
+    /// 没有扩展名的请求找不到文件时返回的文件，用于单页应用
+    pub spa_fallback: Option<PathBuf>,
+    /// 返回SPA回退文件时的状态码，默认为200
+    pub spa_fallback_status: StatusCode,
+    /// 解析路径（包括重写和打开文件）的超时时间，超时返回Unavailable
+    pub resolve_timeout: Option<Duration>,
+    /// 没有预压缩文件时动态压缩使用的编码，支持gzip和zstd（需要`zstd` feature）
+    /// 编码同时需要在`allowed_encodings`中允许
+    #[cfg(feature = "compression")]
+    pub compress_on_the_fly: AcceptEncoding,
+    /// 按内容的SHA-256生成强ETag，第一次需要额外完整读取一次文件
+    /// 结果按路径、大小和文件标识缓存，修改时间不同时重新计算
+    #[cfg(feature = "digest")]
+    pub strong_etag: bool,
+    #[cfg(feature = "digest")]
+    etag_cache: Arc<Mutex<EtagCache>>,
 }
 
+/// 强ETag缓存最多保存的条目数
+#[cfg(feature = "digest")]
+const ETAG_CACHE_ENTRIES: u64 = 1024;
+
+/// 按路径、大小和文件标识缓存的强ETag及计算时的修改时间
+#[cfg(feature = "digest")]
+type EtagCache = LruCache<(PathBuf, u64, Option<u64>), (Option<SystemTime>, String)>;
+
 /// 重写解析参数的Future
 pub type BoxRewriteFuture = BoxFuture<'static, IoResult<ResolveParams>>;
 
+/// 映射请求路径的函数
+pub type PathMapFn = dyn (Fn(&str) -> Option<String>) + Send + Sync;
+
+/// 检查解析到的文件的函数，`Fn(&ResolvedFile<O::File>) -> Option<ResolveResult<O::File>>`都实现了它
+/// 以`FileOpener`为参数，`Resolver`的定义因此不需要`O: FileOpener`约束
+pub trait OnResolvedFn<O>: Send + Sync {
+    fn call(&self, file: &ResolvedFile<O::File>) -> Option<ResolveResult<O::File>>
+    where
+        O: FileOpener;
+}
+
+impl<O, H> OnResolvedFn<O> for H
+where
+    O: FileOpener,
+    H: (Fn(&ResolvedFile<O::File>) -> Option<ResolveResult<O::File>>) + Send + Sync,
+{
+    fn call(&self, file: &ResolvedFile<O::File>) -> Option<ResolveResult<O::File>> {
+        self(file)
+    }
+}
+
 /// 解析所需参数
 #[derive(Debug, Clone)]
 pub struct ResolveParams {
@@ -68,14 +251,58 @@ pub struct ResolveParams {
 #[derive(Debug)]
 pub enum ResolveResult<F = File> {
     MethodNotMatched,
+    BadRequest,
     NotFound,
     PermissionDenied,
     IsDirectory { redirect_to: String },
+    /// 重定向到规范地址
+    Redirect { redirect_to: String },
+    /// 解析超时
+    Unavailable,
+    /// 开启`autoindex`时没有index的目录
+    Directory { entries: Vec<DirectoryEntry> },
+    /// 对存在的资源发起的OPTIONS请求，`accept_ranges`表示目标是支持范围请求的文件
+    OptionsAllowed { accept_ranges: bool },
     Found(ResolvedFile<F>),
 }
 
+/// 默认允许的请求方法，`allowed_methods`为空时Allow头也列出这些方法
+pub const DEFAULT_ALLOWED_METHODS: &[Method] = &[Method::GET, Method::HEAD, Method::OPTIONS];
+
+/// 能够处理的请求方法，`allowed_methods`和Allow头中的其它方法都会被忽略
+pub(crate) fn is_servable_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// 由文件路径生成重定向地址，每一段重新做百分号编码
+fn redirect_target(path: &Path, trailing_slash: bool) -> String {
+    let mut target = String::with_capacity(path.as_os_str().len() + 2);
+    for component in path.components() {
+        target.push('/');
+        target.extend(percent_encode(
+            component.as_os_str().as_encoded_bytes(),
+            PATH_SEGMENT,
+        ));
+    }
+    if trailing_slash || target.is_empty() {
+        target.push('/');
+    }
+    target
+}
+
+/// 路径中是否有以`.`开头的部分
+fn is_hidden(path: &Path) -> bool {
+    path.components()
+        .any(|component| component.as_os_str().to_string_lossy().starts_with('.'))
+}
+
 /// 将打开io错误映射为解析错误类型
 fn map_open_err<F>(err: IoError) -> IoResult<ResolveResult<F>> {
+    #[cfg(feature = "tracing")]
+    match err.kind() {
+        IoErrorKind::NotFound => tracing::debug!(error = %err, "file not found"),
+        _ => tracing::warn!(error = %err, "failed to open file"),
+    }
     match err.kind() {
         IoErrorKind::NotFound => Ok(ResolveResult::NotFound),
         IoErrorKind::PermissionDenied => Ok(ResolveResult::PermissionDenied),
@@ -83,11 +310,50 @@ fn map_open_err<F>(err: IoError) -> IoResult<ResolveResult<F>> {
     }
 }
 
+/// 在span中记录解析结果
+#[cfg(feature = "tracing")]
+fn record_result<F>(span: &tracing::Span, result: &IoResult<ResolveResult<F>>) {
+    let name = match result {
+        Ok(ResolveResult::MethodNotMatched) => "method_not_matched",
+        Ok(ResolveResult::BadRequest) => "bad_request",
+        Ok(ResolveResult::NotFound) => "not_found",
+        Ok(ResolveResult::PermissionDenied) => "permission_denied",
+        Ok(ResolveResult::IsDirectory { .. }) => "is_directory",
+        Ok(ResolveResult::Redirect { .. }) => "redirect",
+        Ok(ResolveResult::Unavailable) => "unavailable",
+        Ok(ResolveResult::Directory { .. }) => "directory",
+        Ok(ResolveResult::OptionsAllowed { .. }) => "options_allowed",
+        Ok(ResolveResult::Found(_)) => "found",
+        Err(_) => "error",
+    };
+    span.record("result", name);
+    if let Ok(ResolveResult::Found(file)) = result {
+        span.record("size", file.size);
+        if let Some(encoding) = file.encoding {
+            span.record("encoding", tracing::field::debug(encoding));
+        }
+    }
+}
+
 impl Resolver<TokioFileOpener> {
     pub fn new(root: impl Into<PathBuf>) -> Self {
         Self::with_opener(TokioFileOpener::new(root))
     }
 
+    /// 所有请求都解析到同一个文件，如健康检查文件或固定的SPA入口
+    /// Content-Type、预压缩文件和条件请求仍按该文件处理
+    pub fn single_file(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let target = path.file_name().map(|name| {
+            let name = percent_encode(name.as_encoded_bytes(), PATH_SEGMENT);
+            format!("/{name}")
+        });
+        let mut resolver = Self::new(root);
+        resolver.serve_dotfiles = true;
+        resolver.set_path_map(move |_| target.clone());
+        resolver
+    }
 }
 
 impl Resolver<MemoryFs> {
@@ -100,8 +366,37 @@ impl<O: FileOpener> Resolver<O> {
     pub fn with_opener(opener: O) -> Self {
         Self {
             opener: Arc::new(opener),
+            allowed_methods: DEFAULT_ALLOWED_METHODS.to_vec(),
             allowed_encodings: AcceptEncoding::none(),
             rewrite: None,
+            path_map: None,
+            content_type_variants: HashMap::new(),
+            mime_overrides: HashMap::new(),
+            magic_types: Vec::new(),
+            charset: Some("utf-8".to_string()),
+            default_content_type: None,
+            compressible_types: default_compressible_types(),
+            clean_urls: false,
+            on_resolved: None,
+            trailing_slash: TrailingSlash::default(),
+            trailing_dots: TrailingDots::default(),
+            canonical_case_redirect: false,
+            force_encoding: None,
+            sidecar_openers: HashMap::new(),
+            index_files: vec!["index.html".to_string()],
+            autoindex: false,
+            noindex_marker: None,
+            max_depth: None,
+            serve_dotfiles: false,
+            spa_fallback: None,
+            spa_fallback_status: StatusCode::OK,
+            resolve_timeout: None,
+            #[cfg(feature = "compression")]
+            compress_on_the_fly: AcceptEncoding::none(),
+            #[cfg(feature = "digest")]
+            strong_etag: false,
+            #[cfg(feature = "digest")]
+            etag_cache: Arc::default(),
         }
     }
 
@@ -114,14 +409,115 @@ impl<O: FileOpener> Resolver<O> {
         self
     }
 
+    pub fn set_path_map<F>(&mut self, path_map: F) -> &mut Self
+    where
+        F: (Fn(&str) -> Option<String>) + Send + Sync + 'static,
+    {
+        self.path_map = Some(Arc::new(path_map));
+        self
+    }
+
+    /// 设置允许的请求方法，忽略GET、HEAD和OPTIONS以外无法处理的方法
+    pub fn set_allowed_methods(&mut self, methods: impl IntoIterator<Item = Method>) -> &mut Self {
+        self.allowed_methods.clear();
+        for method in methods {
+            if is_servable_method(&method) && !self.allowed_methods.contains(&method) {
+                self.allowed_methods.push(method);
+            }
+        }
+        self
+    }
+
+    pub fn set_on_resolved<H>(&mut self, on_resolved: H) -> &mut Self
+    where
+        H: (Fn(&ResolvedFile<O::File>) -> Option<ResolveResult<O::File>>) + Send + Sync + 'static,
+    {
+        self.on_resolved = Some(Arc::new(on_resolved));
+        self
+    }
+
+    /// 设置单页应用的回退文件，如`/index.html`
+    pub fn set_spa_fallback(&mut self, path: &str) -> &mut Self {
+        self.spa_fallback = Some(RequestedPath::resolve(path).sanitized);
+        self
+    }
+
+    /// 指定某种编码的预压缩文件所在的位置，如gzip文件单独放在`dist-gz/`
+    pub fn set_sidecar_opener(&mut self, encoding: Encoding, opener: O) -> &mut Self {
+        self.sidecar_openers.insert(encoding, Arc::new(opener));
+        self
+    }
+
+    /// 为请求路径注册可选的Content-Type，按顺序作为Accept协商的候选
+    pub fn add_content_type_variants(
+        &mut self,
+        request_path: impl Into<String>,
+        content_types: impl IntoIterator<Item = Mime>,
+    ) -> &mut Self {
+        self.content_type_variants
+            .insert(request_path.into(), content_types.into_iter().collect());
+        self
+    }
+
+    /// 为扩展名指定MIME类型，如`wasm` -> `application/wasm`
+    pub fn add_mime_override(&mut self, extension: &str, mimetype: Mime) -> &mut Self {
+        self.mime_overrides.insert(extension.to_ascii_lowercase(), mimetype);
+        self
+    }
+
+    /// 为文件开头的字节指定MIME类型，用于没有扩展名的私有格式
+    pub fn add_magic_type(&mut self, signature: impl Into<Vec<u8>>, mimetype: Mime) -> &mut Self {
+        self.magic_types.push((signature.into(), mimetype));
+        self
+    }
+
+    /// 是否为可压缩的MIME类型
+    pub fn is_compressible(&self, mimetype: &Mime) -> bool {
+        mimetype
+            .essence_str()
+            .parse::<Mime>()
+            .is_ok_and(|essence| self.compressible_types.contains(&essence))
+    }
+
     /// 解析Request
+    #[deprecated(note = "use `resolve_request`")]
     pub async fn resovle_request<B>(&self, req: &Request<B>) -> IoResult<ResolveResult<O::File>> {
-        // 解析请求方法
-        match *req.method() {
-            Method::HEAD | Method::GET => {}
-            _ => {
-                return Ok(ResolveResult::MethodNotMatched);
-            }
+        self.resolve_request(req).await
+    }
+
+    /// 只解析请求对应文件的元信息，不构建响应体，未解析到文件时返回None
+    pub async fn resolve_metadata<B>(
+        &self,
+        req: &Request<B>,
+    ) -> IoResult<Option<ResolvedMetadata>> {
+        Ok(match self.resolve_request(req).await? {
+            ResolveResult::Found(file) => Some(file.metadata()),
+            _ => None,
+        })
+    }
+
+    /// 解析Request
+    pub async fn resolve_request<B>(&self, req: &Request<B>) -> IoResult<ResolveResult<O::File>> {
+        // OPTIONS只确认资源存在，不返回内容，不在`allowed_methods`中时返回405
+        if req.method() == Method::OPTIONS && self.allowed_methods.contains(&Method::OPTIONS) {
+            let result = self
+                .resolve_path_with(req.uri().path(), AcceptEncoding::none(), false)
+                .await?;
+            return Ok(match self.call_on_resolved(result) {
+                // 替换了状态码的文件不是资源本身，不支持范围请求
+                ResolveResult::Found(file) => ResolveResult::OptionsAllowed {
+                    accept_ranges: file.status.is_none(),
+                },
+                ResolveResult::Directory { .. } => ResolveResult::OptionsAllowed {
+                    accept_ranges: false,
+                },
+                result => result,
+            });
+        }
+
+        // 解析请求方法，与Allow头使用同样的判断
+        if !is_servable_method(req.method()) || !self.allowed_methods.contains(req.method()) {
+            return Ok(ResolveResult::MethodNotMatched);
         }
 
         // 解析编码
@@ -132,7 +528,35 @@ impl<O: FileOpener> Resolver<O> {
                 .map(AcceptEncoding::from_header_value)
                 .unwrap_or(AcceptEncoding::none());
         // 解析路径
-        self.resolve_path(req.uri().path(), accept_encoding).await
+        let mut result = self.resolve_path(req.uri().path(), accept_encoding).await?;
+
+        if let ResolveResult::Found(ref mut file) = result {
+            file.priority = req
+                .headers()
+                .get(PRIORITY)
+                .map(Priority::from_header_value);
+
+            // 根据Accept协商Content-Type
+            if let Some(variants) = self.content_type_variants.get(req.uri().path()) {
+                file.vary.push(header::ACCEPT);
+                let accept = req.headers().get(header::ACCEPT);
+                if let Some(mimetype) = negotiate_content_type(accept, variants) {
+                    let mimetype = set_charset(mimetype.clone(), self.charset.as_deref());
+                    file.content_type = Some(mimetype.to_string());
+                }
+            }
+        }
+        Ok(self.call_on_resolved(result))
+    }
+
+    /// 解析到文件时调用`on_resolved`，由它决定是否替换结果
+    fn call_on_resolved(&self, result: ResolveResult<O::File>) -> ResolveResult<O::File> {
+        match (result, &self.on_resolved) {
+            (ResolveResult::Found(file), Some(on_resolved)) => on_resolved
+                .call(&file)
+                .unwrap_or(ResolveResult::Found(file)),
+            (result, _) => result,
+        }
     }
 
     /// 解析路径
@@ -141,9 +565,71 @@ impl<O: FileOpener> Resolver<O> {
         request_path: &str,
         accept_encoding: AcceptEncoding,
     ) -> IoResult<ResolveResult<O::File>> {
-        let requested_path = RequestedPath::resolve(request_path);
+        self.resolve_path_with(request_path, accept_encoding, true)
+            .await
+    }
+
+    /// 解析路径，`compute_etag`为false时不计算强ETag，用于只确认资源存在的OPTIONS请求
+    async fn resolve_path_with(
+        &self,
+        request_path: &str,
+        accept_encoding: AcceptEncoding,
+        compute_etag: bool,
+    ) -> IoResult<ResolveResult<O::File>> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "resolve_path",
+            path = request_path,
+            result = tracing::field::Empty,
+            encoding = tracing::field::Empty,
+            size = tracing::field::Empty,
+        );
+        let resolve = self.resolve_path_inner(request_path, accept_encoding, compute_etag);
+        #[cfg(feature = "tracing")]
+        let resolve = tracing::Instrument::instrument(resolve, span.clone());
+        let result = match self.resolve_timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, resolve).await {
+                Ok(result) => result,
+                Err(_) => Ok(ResolveResult::Unavailable),
+            },
+            None => resolve.await,
+        };
+        #[cfg(feature = "tracing")]
+        record_result(&span, &result);
+        result
+    }
+
+    async fn resolve_path_inner(
+        &self,
+        request_path: &str,
+        accept_encoding: AcceptEncoding,
+        compute_etag: bool,
+    ) -> IoResult<ResolveResult<O::File>> {
+        let mapped_path;
+        let request_path = match self.path_map {
+            Some(ref path_map) => match path_map(request_path) {
+                Some(path) => {
+                    mapped_path = path;
+                    mapped_path.as_str()
+                }
+                None => return Ok(ResolveResult::NotFound),
+            },
+            None => request_path,
+        };
+        let Some(requested_path) = RequestedPath::resolve_with(request_path, self.trailing_dots)
+        else {
+            return Ok(ResolveResult::BadRequest);
+        };
+        if let Some(max_depth) = self.max_depth {
+            if requested_path.sanitized.components().count() > max_depth {
+                return Ok(ResolveResult::NotFound);
+            }
+        }
+        if !self.serve_dotfiles && is_hidden(&requested_path.sanitized) {
+            return Ok(ResolveResult::NotFound);
+        }
         let ResolveParams {
-            mut path,
+            path,
             is_dir_request,
             accept_encoding,
         } = {
@@ -157,97 +643,333 @@ impl<O: FileOpener> Resolver<O> {
             }
             params
         };
+
+        // 有扩展名的请求视为静态资源，不使用SPA回退
+        let is_asset = path.extension().is_some();
+        let result = self
+            .resolve_params(path, is_dir_request, accept_encoding, compute_etag)
+            .await?;
+        match (result, &self.spa_fallback) {
+            (ResolveResult::NotFound, Some(fallback)) if !is_asset => {
+                match self.opener.open(fallback).await {
+                    Ok(file) if !file.is_dir => {
+                        let mut result = self
+                            .resolve_final(file, fallback.clone(), accept_encoding, compute_etag)
+                            .await?;
+                        if let ResolveResult::Found(ref mut file) = result {
+                            if self.spa_fallback_status != StatusCode::OK {
+                                file.status = Some(self.spa_fallback_status);
+                            }
+                        }
+                        Ok(result)
+                    }
+                    _ => Ok(ResolveResult::NotFound),
+                }
+            }
+            (result, _) => Ok(result),
+        }
+    }
+
+    /// 根据重写后的参数打开文件
+    async fn resolve_params(
+        &self,
+        mut path: PathBuf,
+        is_dir_request: bool,
+        accept_encoding: AcceptEncoding,
+        compute_etag: bool,
+    ) -> IoResult<ResolveResult<O::File>> {
         // 打开文件
         let file = match self.opener.open(&path).await {
             Ok(pair) => pair,
-            Err(err) => return map_open_err(err),
+            Err(err) => {
+                // 无扩展名的请求尝试对应的`.html`文件
+                if self.clean_urls
+                    && err.kind() == IoErrorKind::NotFound
+                    && !is_dir_request
+                    && path.extension().is_none()
+                {
+                    let html_path = path.with_extension("html");
+                    if let Ok(file) = self.opener.open(&html_path).await {
+                        if !file.is_dir {
+                            return self
+                                .resolve_final(file, html_path, accept_encoding, compute_etag)
+                                .await;
+                        }
+                    }
+                }
+                return map_open_err(err);
+            }
         };
 
-        if is_dir_request && !file.is_dir {
-            return Ok(ResolveResult::NotFound);
+        if self.canonical_case_redirect {
+            if let Some(canonical) = self.canonical_case(&path).await {
+                return Ok(ResolveResult::Redirect {
+                    redirect_to: redirect_target(&canonical, is_dir_request),
+                });
+            }
         }
 
-        if !is_dir_request && file.is_dir {
-            let mut target = String::with_capacity(path.as_os_str().len() + 2);
-            target.push('/');
-            for component in path.components() {
-                target.push_str(&component.as_os_str().to_string_lossy());
-                target.push('/');
+        // `.html`文件重定向到无扩展名的地址，无扩展名的同名文件存在时不重定向
+        if self.clean_urls
+            && !is_dir_request
+            && !file.is_dir
+            && path.extension().is_some_and(|ext| ext == "html")
+        {
+            let clean_path = path.with_extension("");
+            if self.opener.open(&clean_path).await.is_err() {
+                return Ok(ResolveResult::Redirect {
+                    redirect_to: redirect_target(&clean_path, false),
+                });
             }
+        }
+
+        if is_dir_request && !file.is_dir {
+            match self.trailing_slash {
+                TrailingSlash::AddToDirectories => return Ok(ResolveResult::NotFound),
+                TrailingSlash::NoRedirect => {}
+                TrailingSlash::RemoveFromFiles => {
+                    return Ok(ResolveResult::Redirect {
+                        redirect_to: redirect_target(&path, false),
+                    });
+                }
+            }
+        }
+
+        if !is_dir_request && file.is_dir && self.trailing_slash != TrailingSlash::NoRedirect {
             return Ok(ResolveResult::IsDirectory {
-                redirect_to: target,
+                redirect_to: redirect_target(&path, true),
             });
         }
 
-        if !is_dir_request {
-            return self.resolve_final(file, path, accept_encoding).await;
+        if !file.is_dir {
+            return self
+                .resolve_final(file, path, accept_encoding, compute_etag)
+                .await;
         }
 
-        path.push("index.html");
-        let file = match self.opener.open(&path).await {
-            Ok(pair) => pair,
-            Err(err) => return map_open_err(err),
-        };
+        if let Some(ref marker) = self.noindex_marker {
+            let marker_file = self.opener.open(&path.join(marker)).await;
+            if marker_file.is_ok_and(|file| !file.is_dir) {
+                return Ok(ResolveResult::NotFound);
+            }
+        }
 
-        if file.is_dir {
+        // 按顺序尝试index文件，使用第一个存在且不是目录的
+        for index_file in &self.index_files {
+            path.push(index_file);
+            match self.opener.open(&path).await {
+                Ok(file) if !file.is_dir => {
+                    let content_location = (!is_dir_request).then(|| redirect_target(&path, false));
+                    let mut result = self
+                        .resolve_final(file, path, accept_encoding, compute_etag)
+                        .await?;
+                    if let ResolveResult::Found(ref mut file) = result {
+                        file.content_location = content_location;
+                    }
+                    return Ok(result);
+                }
+                Ok(_) => {}
+                Err(err) if err.kind() == IoErrorKind::NotFound => {}
+                Err(err) => return map_open_err(err),
+            }
+            path.pop();
+        }
+
+        if !self.autoindex {
             return Ok(ResolveResult::NotFound);
         }
 
-        self.resolve_final(file, path, accept_encoding).await
+        // 没有index时列出目录
+        match self.opener.read_dir(&path).await {
+            Ok(mut entries) => {
+                if !self.serve_dotfiles {
+                    entries.retain(|entry| !entry.name.starts_with('.'));
+                }
+                Ok(ResolveResult::Directory { entries })
+            }
+            Err(err) if err.kind() == IoErrorKind::Unsupported => Ok(ResolveResult::NotFound),
+            Err(err) => map_open_err(err),
+        }
+    }
+
+    /// 逐层列出目录，得到磁盘上实际的大小写，与`path`不同时返回
+    /// 无法列出目录或找不到唯一匹配的条目时返回None
+    async fn canonical_case(&self, path: &Path) -> Option<PathBuf> {
+        let mut canonical = PathBuf::new();
+        let mut changed = false;
+        for component in path.components() {
+            let name = component.as_os_str().to_str()?;
+            let entries = self.opener.read_dir(&canonical).await.ok()?;
+            if !entries.iter().any(|entry| entry.name == name) {
+                let lower = name.to_lowercase();
+                let mut matched = entries
+                    .iter()
+                    .filter(|entry| entry.name.to_lowercase() == lower);
+                let entry = matched.next()?;
+                if matched.next().is_some() {
+                    return None;
+                }
+                canonical.push(&entry.name);
+                changed = true;
+            } else {
+                canonical.push(name);
+            }
+        }
+        changed.then_some(canonical)
     }
 
     /// 解析最终结果
+    #[cfg_attr(not(feature = "digest"), allow(unused_variables))]
     async fn resolve_final(
         &self,
         file: FileWithMetadata<O::File>,
         path: PathBuf,
         accept_encoding: AcceptEncoding,
+        compute_etag: bool,
     ) -> IoResult<ResolveResult<O::File>> {
-        let mimetype = MimeGuess::from_path(&path)
-            .first()
-            .map(|mimetype| set_charset(mimetype).to_string());
-
-        if accept_encoding.zstd {
-            let mut zstd_path = path.clone().into_os_string();
-            zstd_path.push("zst");
-            if let Ok(file) = self.opener.open(zstd_path.as_ref()).await {
-                return Ok(ResolveResult::Found(ResolvedFile::new(
-                    file,
-                    zstd_path.into(),
-                    mimetype,
-                    Some(Encoding::Zstd),
-                )));
-            }
-        }
+        let guessed = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.mime_overrides.get(&ext.to_ascii_lowercase()))
+            .cloned()
+            .or_else(|| MimeGuess::from_path(&path).first());
+        let guessed = match guessed {
+            None if !self.magic_types.is_empty() => self.sniff_magic_type(&path).await?,
+            guessed => guessed,
+        };
+        #[cfg(any(feature = "compression", feature = "tracing"))]
+        let compressible = guessed
+            .as_ref()
+            .is_some_and(|mimetype| self.is_compressible(mimetype));
+        let mimetype = guessed
+            .map(|mimetype| set_charset(mimetype, self.charset.as_deref()).to_string())
+            .or_else(|| self.default_content_type.clone());
 
-        if accept_encoding.br {
-            let mut br_path = path.clone().into_os_string();
-            br_path.push(".br");
-            if let Ok(file) = self.opener.open(br_path.as_ref()).await {
-                return Ok(ResolveResult::Found(ResolvedFile::new(
-                    file,
-                    br_path.into(),
-                    mimetype,
-                    Some(Encoding::Br),
-                )));
+        // 按客户端偏好依次尝试预压缩文件，存在预压缩文件时不论类型都使用
+        let mut encodings = accept_encoding.preferred();
+        // 强制的编码不受Accept-Encoding的限制
+        if let Some(forced) = self.force_encoding {
+            encodings.retain(|encoding| *encoding != forced);
+            encodings.insert(0, forced);
+        }
+        let mut sidecar = None;
+        for encoding in encodings {
+            let opener = self.sidecar_openers.get(&encoding).unwrap_or(&self.opener);
+            sidecar = open_encoded(&**opener, &path, &[encoding]).await;
+            if sidecar.is_some() {
+                break;
             }
         }
-        if accept_encoding.gzip {
-            let mut gzip_path = path.clone().into_os_string();
-            gzip_path.push(".gz");
-            if let Ok(file) = self.opener.open(gzip_path.as_ref()).await {
-                return Ok(ResolveResult::Found(ResolvedFile::new(
-                    file,
-                    gzip_path.into(),
-                    mimetype,
-                    Some(Encoding::Gzip),
-                )));
+        // 只有可压缩的类型才动态压缩，按客户端偏好选择支持动态压缩的编码
+        #[cfg(feature = "compression")]
+        let on_the_fly = (accept_encoding & self.compress_on_the_fly)
+            .preferred()
+            .into_iter()
+            .find(|encoding| compressible && encoding.can_compress_on_the_fly());
+        let mut resolved = match sidecar {
+            Some((file, encoded_path, encoding)) => {
+                let mut resolved = ResolvedFile::new(file, encoded_path, mimetype, Some(encoding));
+                resolved.logical_path = path;
+                resolved
+            }
+            // 可压缩的类型没有预压缩文件时动态压缩
+            #[cfg(feature = "compression")]
+            None if on_the_fly.is_some() => {
+                let mut resolved = ResolvedFile::new(file, path, mimetype, on_the_fly);
+                resolved.compress = true;
+                resolved
             }
+            None => ResolvedFile::new(file, path, mimetype, None),
+        };
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            compressible,
+            encoding = ?resolved.encoding,
+            "negotiated encoding"
+        );
+
+        // 是否压缩取决于Accept-Encoding，共享缓存需要区分
+        if !self.allowed_encodings.is_empty() {
+            resolved.vary.push(header::ACCEPT_ENCODING);
+        }
+
+        #[cfg(feature = "digest")]
+        if self.strong_etag && compute_etag {
+            // 预压缩文件可能来自单独的opener
+            let opener = match resolved.encoding {
+                Some(encoding) if resolved.path != resolved.logical_path => {
+                    self.sidecar_openers.get(&encoding).unwrap_or(&self.opener)
+                }
+                _ => &self.opener,
+            };
+            let key = (resolved.path.clone(), resolved.size, resolved.unique_id);
+            let etag = self.content_etag(&**opener, key, resolved.modified).await?;
+            resolved.etag = Some(etag);
+        }
+
+        Ok(ResolveResult::Found(resolved))
+    }
+
+    /// 另外打开一次文件读取开头的字节，与`magic_types`逐个比较
+    async fn sniff_magic_type(&self, path: &Path) -> IoResult<Option<Mime>> {
+        let limit = self
+            .magic_types
+            .iter()
+            .map(|(signature, _)| signature.len())
+            .max()
+            .unwrap_or(0);
+        let file = self.opener.open(path).await?;
+        let mut stream =
+            FileBytesStream::new_with_limit(file.handle.into_file_access(), limit as u64);
+        let mut prefix = Vec::with_capacity(limit);
+        while let Some(chunk) = stream.next().await {
+            prefix.extend_from_slice(&chunk?);
+        }
+        Ok(self
+            .magic_types
+            .iter()
+            .find(|(signature, _)| prefix.starts_with(signature))
+            .map(|(_, mimetype)| mimetype.clone()))
+    }
+
+    /// 计算文件内容的强ETag，另外打开一次文件读取内容，不影响用于输出的句柄
+    #[cfg(feature = "digest")]
+    async fn content_etag(
+        &self,
+        opener: &O,
+        key: (PathBuf, u64, Option<u64>),
+        modified: Option<SystemTime>,
+    ) -> IoResult<String> {
+        use sha2::{Digest, Sha256};
+
+        if let Some((_, etag)) = self
+            .etag_cache
+            .lock()
+            .unwrap()
+            .get(&key, |(cached, _)| *cached == modified)
+        {
+            return Ok(etag.clone());
+        }
+
+        let opened = opener.open(&key.0).await?;
+        let mut hasher = Sha256::new();
+        let mut stream =
+            FileBytesStream::new_with_limit(opened.handle.into_file_access(), opened.size);
+        while let Some(chunk) = stream.next().await {
+            hasher.update(chunk?);
         }
+        let etag = format!("\"{:x}\"", hasher.finalize());
 
-        Ok(ResolveResult::Found(ResolvedFile::new(
-            file, path, mimetype, None,
-        )))
+        // 两次打开之间文件发生变化时不缓存
+        if opened.size == key.1 && opened.modified == modified {
+            self.etag_cache.lock().unwrap().insert(
+                key,
+                (modified, etag.clone()),
+                1,
+                ETAG_CACHE_ENTRIES,
+            );
+        }
+        Ok(etag)
     }
 }
 
@@ -255,28 +977,141 @@ impl<O> Clone for Resolver<O> {
     fn clone(&self) -> Self {
         Self {
             opener: self.opener.clone(),
+            allowed_methods: self.allowed_methods.clone(),
             allowed_encodings: self.allowed_encodings,
             rewrite: self.rewrite.clone(),
+            path_map: self.path_map.clone(),
+            content_type_variants: self.content_type_variants.clone(),
+            mime_overrides: self.mime_overrides.clone(),
+            magic_types: self.magic_types.clone(),
+            charset: self.charset.clone(),
+            default_content_type: self.default_content_type.clone(),
+            compressible_types: self.compressible_types.clone(),
+            clean_urls: self.clean_urls,
+            on_resolved: self.on_resolved.clone(),
+            trailing_slash: self.trailing_slash,
+            trailing_dots: self.trailing_dots,
+            canonical_case_redirect: self.canonical_case_redirect,
+            force_encoding: self.force_encoding,
+            sidecar_openers: self.sidecar_openers.clone(),
+            index_files: self.index_files.clone(),
+            autoindex: self.autoindex,
+            noindex_marker: self.noindex_marker.clone(),
+            max_depth: self.max_depth,
+            serve_dotfiles: self.serve_dotfiles,
+            spa_fallback: self.spa_fallback.clone(),
+            spa_fallback_status: self.spa_fallback_status,
+            resolve_timeout: self.resolve_timeout,
+            #[cfg(feature = "compression")]
+            compress_on_the_fly: self.compress_on_the_fly,
+            #[cfg(feature = "digest")]
+            strong_etag: self.strong_etag,
+            #[cfg(feature = "digest")]
+            etag_cache: self.etag_cache.clone(),
         }
     }
 }
 
-/// 编码
+/// 末尾斜杠的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailingSlash {
+    /// 目录请求缺少末尾斜杠时301重定向，文件请求带末尾斜杠时返回NotFound
+    #[default]
+    AddToDirectories,
+    /// 不重定向：目录直接返回其中的index并设置`Content-Location`，文件忽略末尾斜杠
+    /// 此时index中的相对链接会以上一级目录为基准解析
+    NoRedirect,
+    /// 目录同`AddToDirectories`，文件请求带末尾斜杠时301重定向到去掉斜杠的地址
+    RemoveFromFiles,
+}
+
+/// 路径片段末尾的`.`和空格的处理方式
+/// Windows打开文件时会忽略它们，`foo.txt.`实际打开的是`foo.txt`，可能绕过按扩展名做的判断
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingDots {
+    /// 原样保留
+    Keep,
+    /// 去掉末尾的`.`和空格，保证按扩展名等做的判断与实际打开的文件一致
+    Normalize,
+    /// 返回BadRequest
+    Reject,
+}
+
+impl Default for TrailingDots {
+    /// Windows上默认为`Normalize`，其它平台默认为`Keep`
+    fn default() -> Self {
+        if cfg!(windows) {
+            Self::Normalize
+        } else {
+            Self::Keep
+        }
+    }
+}
+
+/// 编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Encoding {
     Gzip,
     Br,
     Zstd,
+    Deflate,
 }
 
 impl Encoding {
-    pub fn to_header_value(&self) -> HeaderValue {
-        HeaderValue::from_static(match self {
+    pub fn as_str(&self) -> &'static str {
+        match self {
             Encoding::Gzip => "gzip",
             Encoding::Br => "br",
             Encoding::Zstd => "zstd",
-        })
+            Encoding::Deflate => "deflate",
+        }
     }
+
+    pub fn to_header_value(&self) -> HeaderValue {
+        HeaderValue::from_static(self.as_str())
+    }
+
+    /// 是否支持动态压缩，gzip需要`compression` feature，zstd需要`zstd` feature
+    pub fn can_compress_on_the_fly(&self) -> bool {
+        match self {
+            Encoding::Gzip => cfg!(feature = "compression"),
+            Encoding::Zstd => cfg!(feature = "zstd"),
+            Encoding::Br | Encoding::Deflate => false,
+        }
+    }
+
+    /// 预压缩文件的扩展名，deflate使用zlib格式的`.zz`
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gz",
+            Encoding::Br => "br",
+            Encoding::Zstd => "zst",
+            Encoding::Deflate => "zz",
+        }
+    }
+
+    /// 预压缩文件的路径，如`app.js` -> `app.js.br`
+    pub fn sidecar_path(&self, path: &Path) -> PathBuf {
+        let mut sidecar_path = path.as_os_str().to_owned();
+        sidecar_path.push(".");
+        sidecar_path.push(self.extension());
+        sidecar_path.into()
+    }
+}
+
+/// 按`encodings`的顺序依次尝试打开`path`的预压缩文件，返回第一个成功打开的文件、路径及编码
+pub async fn open_encoded<O: FileOpener>(
+    opener: &O,
+    path: &Path,
+    encodings: &[Encoding],
+) -> Option<(FileWithMetadata<O::File>, PathBuf, Encoding)> {
+    for encoding in encodings {
+        let sidecar_path = encoding.sidecar_path(path);
+        if let Ok(file) = opener.open(&sidecar_path).await {
+            return Some((file, sidecar_path, *encoding));
+        }
+    }
+    None
 }
 
 /// 编码状态
@@ -285,6 +1120,12 @@ pub struct AcceptEncoding {
     pub gzip: bool,
     pub br: bool,
     pub zstd: bool,
+    pub deflate: bool,
+    /// 各编码在Accept-Encoding中的权重（q值），不接受时为0
+    pub gzip_q: f32,
+    pub br_q: f32,
+    pub zstd_q: f32,
+    pub deflate_q: f32,
 }
 
 impl AcceptEncoding {
@@ -293,31 +1134,85 @@ impl AcceptEncoding {
             gzip: true,
             br: true,
             zstd: true,
+            deflate: true,
+            gzip_q: 1.0,
+            br_q: 1.0,
+            zstd_q: 1.0,
+            deflate_q: 1.0,
         }
     }
 
+    /// 是否不接受任何编码
+    pub fn is_empty(&self) -> bool {
+        !(self.gzip || self.br || self.zstd || self.deflate)
+    }
+
     pub const fn none() -> Self {
         Self {
             gzip: false,
             br: false,
             zstd: false,
+            deflate: false,
+            gzip_q: 0.0,
+            br_q: 0.0,
+            zstd_q: 0.0,
+            deflate_q: 0.0,
         }
     }
 
+    /// 解析Accept-Encoding，`q=0`表示不接受该编码，没有q值时权重为1
     pub fn from_header_value(value: &HeaderValue) -> Self {
         let mut res = Self::none();
         if let Ok(value) = value.to_str() {
             for enc in value.split(",") {
-                match enc.split(";").next().unwrap().trim() {
-                    "gzip" => res.gzip = true,
-                    "br" => res.br = true,
-                    "zstd" => res.zstd = true,
+                let mut parts = enc.split(";");
+                let name = parts.next().unwrap().trim();
+                let q = parts
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0)
+                    .clamp(0.0, 1.0);
+                match name {
+                    "gzip" => (res.gzip, res.gzip_q) = (q > 0.0, q),
+                    "br" => (res.br, res.br_q) = (q > 0.0, q),
+                    "zstd" => (res.zstd, res.zstd_q) = (q > 0.0, q),
+                    "deflate" => (res.deflate, res.deflate_q) = (q > 0.0, q),
                     _ => {}
                 }
             }
         }
         res
     }
+
+    /// 编码的权重，不接受时为0
+    pub fn weight(&self, encoding: Encoding) -> f32 {
+        let (accepted, q) = match encoding {
+            Encoding::Gzip => (self.gzip, self.gzip_q),
+            Encoding::Br => (self.br, self.br_q),
+            Encoding::Zstd => (self.zstd, self.zstd_q),
+            Encoding::Deflate => (self.deflate, self.deflate_q),
+        };
+        if accepted {
+            q
+        } else {
+            0.0
+        }
+    }
+
+    /// 按权重从高到低排列接受的编码，权重相同时按zstd、br、gzip、deflate的顺序
+    pub fn preferred(&self) -> Vec<Encoding> {
+        let mut encodings: Vec<_> = [
+            Encoding::Zstd,
+            Encoding::Br,
+            Encoding::Gzip,
+            Encoding::Deflate,
+        ]
+        .into_iter()
+        .filter(|encoding| self.weight(*encoding) > 0.0)
+        .collect();
+        encodings.sort_by(|a, b| self.weight(*b).total_cmp(&self.weight(*a)));
+        encodings
+    }
 }
 
 impl BitAnd for AcceptEncoding {
@@ -327,16 +1222,274 @@ impl BitAnd for AcceptEncoding {
             gzip: self.gzip && rhs.gzip,
             br: self.br && rhs.br,
             zstd: self.zstd && rhs.zstd,
+            deflate: self.deflate && rhs.deflate,
+            gzip_q: self.gzip_q.min(rhs.gzip_q),
+            br_q: self.br_q.min(rhs.br_q),
+            zstd_q: self.zstd_q.min(rhs.zstd_q),
+            deflate_q: self.deflate_q.min(rhs.deflate_q),
+        }
+    }
+}
+
+/// 默认的可压缩MIME类型
+pub fn default_compressible_types() -> HashSet<Mime> {
+    [
+        "text/html",
+        "text/css",
+        "text/plain",
+        "text/csv",
+        "text/xml",
+        "text/javascript",
+        "application/javascript",
+        "application/json",
+        "application/manifest+json",
+        "application/xml",
+        "image/svg+xml",
+    ]
+    .iter()
+    .map(|mimetype| mimetype.parse().unwrap())
+    .collect()
+}
+
+/// 从候选类型中选出Accept中权重最高的一个，权重相同时取靠前的候选
+/// 没有Accept头时返回第一个候选
+fn negotiate_content_type<'a>(accept: Option<&HeaderValue>, variants: &'a [Mime]) -> Option<&'a Mime> {
+    let accept = match accept.and_then(|v| v.to_str().ok()) {
+        Some(accept) => accept,
+        None => return variants.first(),
+    };
+
+    let ranges: Vec<(Mime, f32)> = accept
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let range = parts.next()?.trim().parse::<Mime>().ok()?;
+            let q = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some((range, q))
+        })
+        .collect();
+
+    let mut best: Option<(&Mime, f32)> = None;
+    for variant in variants {
+        let q = ranges
+            .iter()
+            .filter(|(range, _)| {
+                (range.type_() == mime::STAR || range.type_() == variant.type_())
+                    && (range.subtype() == mime::STAR || range.subtype() == variant.subtype())
+            })
+            .map(|(_, q)| *q)
+            .fold(None, |acc: Option<f32>, q| Some(acc.map_or(q, |acc| acc.max(q))));
+        if let Some(q) = q.filter(|q| *q > 0.0) {
+            if best.is_none_or(|(_, best_q)| q > best_q) {
+                best = Some((variant, q));
+            }
         }
     }
+    best.map(|(variant, _)| variant)
 }
 
-fn set_charset(mimetype: Mime) -> Mime {
-    if mimetype == mime::APPLICATION_JAVASCRIPT {
-        return mime::APPLICATION_JAVASCRIPT_UTF_8;
+pub const PRIORITY: HeaderName = HeaderName::from_static("priority");
+
+/// HTTP扩展优先级（RFC 9218）
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Priority {
+    /// 紧急程度，0~7，越小越紧急
+    pub urgency: u8,
+    /// 是否可以增量处理
+    pub incremental: bool,
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Self {
+            urgency: 3,
+            incremental: false,
+        }
+    }
+}
+
+impl Priority {
+    /// 解析`Priority`请求头，无法识别的参数会被忽略
+    pub fn from_header_value(value: &HeaderValue) -> Self {
+        let mut res = Self::default();
+        if let Ok(value) = value.to_str() {
+            for item in value.split(',') {
+                let mut kv = item.split(';').next().unwrap().splitn(2, '=');
+                let key = kv.next().unwrap().trim();
+                let value = kv.next().map(str::trim);
+                match (key, value) {
+                    ("u", Some(u)) => {
+                        if let Some(u) = u.parse::<u8>().ok().filter(|u| *u <= 7) {
+                            res.urgency = u;
+                        }
+                    }
+                    ("i", None) | ("i", Some("?1")) => res.incremental = true,
+                    ("i", Some("?0")) => res.incremental = false,
+                    _ => {}
+                }
+            }
+        }
+        res
+    }
+
+    pub fn to_header_value(&self) -> HeaderValue {
+        if self.incremental {
+            HeaderValue::from_str(&format!("u={}, i", self.urgency)).unwrap()
+        } else {
+            HeaderValue::from_str(&format!("u={}", self.urgency)).unwrap()
+        }
     }
-    if mimetype == mime::TEXT_JAVASCRIPT {
-        return "text/javascript; charset=utf-8".parse().unwrap();
+}
+
+fn set_charset(mimetype: Mime, charset: Option<&str>) -> Mime {
+    let Some(charset) = charset else {
+        return mimetype;
+    };
+    if mimetype.get_param(mime::CHARSET).is_some() {
+        return mimetype;
+    }
+    let is_text = mimetype.type_() == mime::TEXT
+        || mimetype.essence_str() == "application/javascript"
+        || mimetype.essence_str() == "application/json";
+    if !is_text {
+        return mimetype;
+    }
+    format!("{}; charset={}", mimetype.essence_str(), charset)
+        .parse()
+        .unwrap_or(mimetype)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::Mutex;
+
+    use futures_util::future::{ready, Ready};
+    use hyper::body::Bytes;
+
+    use super::*;
+
+    /// 只有`app.js.br`的opener，记录依次尝试打开的路径
+    #[derive(Default)]
+    struct BrOnlyOpener {
+        opened: Mutex<Vec<PathBuf>>,
+    }
+
+    impl FileOpener for BrOnlyOpener {
+        type File = Cursor<Bytes>;
+        type Future = Ready<IoResult<FileWithMetadata<Self::File>>>;
+
+        fn open(&self, path: &Path) -> Self::Future {
+            self.opened.lock().unwrap().push(path.to_path_buf());
+            if path != Path::new("app.js.br") {
+                return ready(Err(IoErrorKind::NotFound.into()));
+            }
+            ready(Ok(FileWithMetadata {
+                handle: Cursor::new(Bytes::from_static(b"br")),
+                size: 2,
+                modified: None,
+                is_dir: false,
+                unique_id: None,
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn open_encoded_stops_at_first_sidecar() {
+        let opener = BrOnlyOpener::default();
+        let encodings = [Encoding::Zstd, Encoding::Br, Encoding::Gzip];
+        let (file, path, encoding) = open_encoded(&opener, Path::new("app.js"), &encodings)
+            .await
+            .expect("no sidecar found");
+        assert_eq!(encoding, Encoding::Br);
+        assert_eq!(path, Path::new("app.js.br"));
+        assert_eq!(file.size, 2);
+        // 打开`.br`之后不再尝试gzip
+        assert_eq!(
+            *opener.opened.lock().unwrap(),
+            [PathBuf::from("app.js.zst"), PathBuf::from("app.js.br")]
+        );
+    }
+
+    #[tokio::test]
+    async fn open_encoded_without_sidecar() {
+        let opener = BrOnlyOpener::default();
+        let encodings = [Encoding::Gzip, Encoding::Deflate];
+        assert!(open_encoded(&opener, Path::new("app.js"), &encodings)
+            .await
+            .is_none());
+        assert!(open_encoded(&opener, Path::new("app.js"), &[])
+            .await
+            .is_none());
+    }
+
+    fn priority(value: &'static str) -> Priority {
+        Priority::from_header_value(&HeaderValue::from_static(value))
+    }
+
+    #[test]
+    fn priority_urgency_and_incremental() {
+        let expected = Priority {
+            urgency: 3,
+            incremental: true,
+        };
+        assert_eq!(priority("u=3, i"), expected);
+        assert_eq!(priority("i, u=3"), expected);
+        assert_eq!(priority("u=3,i=?1"), expected);
+        assert_eq!(
+            priority("u=0"),
+            Priority {
+                urgency: 0,
+                incremental: false,
+            }
+        );
+        assert_eq!(priority("u=3, i").to_header_value(), "u=3, i");
+    }
+
+    #[test]
+    fn priority_ignores_malformed_values() {
+        // 无法识别的值都保持默认的u=3、非增量
+        for value in ["", "u=8", "u=-1", "u=x", "u", "i=maybe", "x=1, y", ",,;"] {
+            assert_eq!(priority(value), Priority::default(), "{value}");
+        }
+        // 合法的参数不受其它无效参数影响
+        assert_eq!(
+            priority("u=9, u=1, foo=bar, i=?0"),
+            Priority {
+                urgency: 1,
+                incremental: false,
+            }
+        );
+    }
+
+    fn accept_encoding(value: &'static str) -> AcceptEncoding {
+        AcceptEncoding::from_header_value(&HeaderValue::from_static(value))
+    }
+
+    #[test]
+    fn accept_encoding_q_zero_rejects() {
+        let accept = accept_encoding("gzip;q=0, br;q=1.0");
+        assert!(!accept.gzip);
+        assert!(accept.br);
+        assert!(!accept.zstd);
+        assert_eq!(accept.weight(Encoding::Gzip), 0.0);
+        assert_eq!(accept.weight(Encoding::Br), 1.0);
+        assert_eq!(accept.preferred(), [Encoding::Br]);
+    }
+
+    #[test]
+    fn accept_encoding_orders_by_q() {
+        let accept = accept_encoding("gzip;q=0.8, br;q=0.5, zstd");
+        assert_eq!(
+            accept.preferred(),
+            [Encoding::Zstd, Encoding::Gzip, Encoding::Br]
+        );
+        // 无效的q值按1处理，超出范围的q值被截断
+        let accept = accept_encoding("gzip;q=abc, br;q=2");
+        assert_eq!(accept.weight(Encoding::Gzip), 1.0);
+        assert_eq!(accept.weight(Encoding::Br), 1.0);
     }
-    mimetype
 }
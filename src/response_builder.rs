@@ -1,13 +1,50 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use http::{
-    header, response::Builder as HttpResponseBuilder, HeaderMap, Method, Request, Response, Result, StatusCode, Uri
+    header, response::Builder as HttpResponseBuilder, HeaderMap, HeaderName, HeaderValue, Method, Request, Response, Result, StatusCode, Uri
 };
+use hyper::body::Bytes;
+use rand::RngCore;
 
-use crate::{body::Body, resolve::ResolveResult, util::FileResponseBuilder, vfs::IntoFileAccess};
+#[cfg(feature = "compression")]
+use crate::util::CompressionLoad;
+use crate::{
+    body::Body,
+    resolve::{is_servable_method, ResolveResult, DEFAULT_ALLOWED_METHODS},
+    util::{
+        render_autoindex, render_autoindex_json, BodyWrapper, ByteBudget, CacheControl,
+        ContentDisposition, Cors, FileResponseBuilder,
+    },
+    vfs::IntoFileAccess,
+};
 
 #[derive(Clone, Debug, Default)]
 pub struct ResponseBuilder<'a> {
     pub path: &'a str,
     pub query: Option<&'a str>,
+    /// 查询字符串的最大长度，超出时返回400，避免重定向时生成过长的Location
+    pub max_query_length: Option<usize>,
+    /// 405响应Allow头中的方法，为空时使用`DEFAULT_ALLOWED_METHODS`
+    pub allowed_methods: &'a [Method],
+    /// 不允许的方法返回501而不是405
+    pub method_not_implemented: bool,
+    /// `/favicon.ico`不存在时返回的内容及其Content-Type
+    pub default_favicon: Option<(Bytes, String)>,
+    /// 各错误状态码对应的响应体及其Content-Type，没有配置时响应体为空
+    pub error_pages: Arc<HashMap<StatusCode, (Bytes, String)>>,
+    /// 为Some时目录列表输出为JSON，值为其Content-Type，如`application/json`
+    pub autoindex_json: Option<String>,
+    /// 请求头，用于回显请求ID
+    pub request_headers: Option<&'a HeaderMap>,
+    /// 在响应中回显的请求ID头，如`X-Request-Id`
+    pub request_id_header: Option<HeaderName>,
+    /// 请求中没有请求ID时生成一个
+    pub generate_request_id: bool,
+    /// 追加到所有响应中的头部，如`X-Content-Type-Options`
+    pub extra_headers: Arc<HeaderMap>,
+    /// 额外的头部覆盖响应中已有的同名头部
+    pub override_headers: bool,
     pub file_response_builder: FileResponseBuilder,
 }
 
@@ -28,6 +65,7 @@ impl<'a> ResponseBuilder<'a> {
         headers: &'a HeaderMap,
     ) -> &mut Self {
         self.request_uri(uri);
+        self.request_headers = Some(headers);
         self.file_response_builder.request_parts(method, headers);
         self
     }
@@ -43,6 +81,119 @@ impl<'a> ResponseBuilder<'a> {
         self
     }
 
+    pub fn cache_control(&mut self, value: Option<CacheControl>) -> &mut Self {
+        self.file_response_builder.cache_control(value);
+        self
+    }
+
+    pub fn default_cache_control(&mut self, value: Option<String>) -> &mut Self {
+        self.file_response_builder.default_cache_control(value);
+        self
+    }
+
+    pub fn cors(&mut self, value: Option<Cors>) -> &mut Self {
+        self.file_response_builder.cors(value);
+        self
+    }
+
+    pub fn content_disposition(&mut self, value: Option<ContentDisposition>) -> &mut Self {
+        self.file_response_builder.content_disposition(value);
+        self
+    }
+
+    pub fn body_wrapper(&mut self, value: Option<BodyWrapper>) -> &mut Self {
+        self.file_response_builder.body_wrapper(value);
+        self
+    }
+
+    pub fn byte_budget(&mut self, value: Option<ByteBudget>) -> &mut Self {
+        self.file_response_builder.byte_budget(value);
+        self
+    }
+
+    pub fn chunk_size(&mut self, value: Option<usize>) -> &mut Self {
+        self.file_response_builder.chunk_size(value);
+        self
+    }
+
+    #[cfg(feature = "digest")]
+    pub fn digest_trailer(&mut self, value: bool) -> &mut Self {
+        self.file_response_builder.digest_trailer(value);
+        self
+    }
+
+    pub fn max_ranges(&mut self, value: Option<usize>) -> &mut Self {
+        self.file_response_builder.max_ranges(value);
+        self
+    }
+
+    #[cfg(feature = "zstd")]
+    pub fn zstd_level(&mut self, value: i32) -> &mut Self {
+        self.file_response_builder.zstd_level(value);
+        self
+    }
+
+    #[cfg(feature = "compression")]
+    pub fn compression_load(&mut self, value: Option<CompressionLoad>) -> &mut Self {
+        self.file_response_builder.compression_load(value);
+        self
+    }
+
+    pub fn echo_priority(&mut self, value: bool) -> &mut Self {
+        self.file_response_builder.echo_priority(value);
+        self
+    }
+
+    pub fn served_file_extension(&mut self, value: bool) -> &mut Self {
+        self.file_response_builder.served_file_extension(value);
+        self
+    }
+
+    pub fn allowed_methods(&mut self, value: &'a [Method]) -> &mut Self {
+        self.allowed_methods = value;
+        self
+    }
+
+    pub fn method_not_implemented(&mut self, value: bool) -> &mut Self {
+        self.method_not_implemented = value;
+        self
+    }
+
+    pub fn default_favicon(&mut self, value: Option<(Bytes, String)>) -> &mut Self {
+        self.default_favicon = value;
+        self
+    }
+
+    pub fn error_pages(&mut self, value: Arc<HashMap<StatusCode, (Bytes, String)>>) -> &mut Self {
+        self.error_pages = value;
+        self
+    }
+
+    pub fn autoindex_json(&mut self, value: Option<String>) -> &mut Self {
+        self.autoindex_json = value;
+        self
+    }
+
+    pub fn request_id_header(&mut self, value: Option<HeaderName>) -> &mut Self {
+        self.request_id_header = value;
+        self
+    }
+
+    pub fn generate_request_id(&mut self, value: bool) -> &mut Self {
+        self.generate_request_id = value;
+        self
+    }
+
+    pub fn extra_headers(&mut self, value: Arc<HeaderMap>) -> &mut Self {
+        self.extra_headers = value;
+        self
+    }
+
+    pub fn override_headers(&mut self, value: bool) -> &mut Self {
+        self.override_headers = value;
+        self
+    }
+
     pub fn path(&mut self, value: &'a str) -> &mut Self {
         self.path = value;
         self
@@ -53,24 +204,233 @@ impl<'a> ResponseBuilder<'a> {
         self
     }
 
+    /// 生成Allow头，只列出能够处理的方法
+    pub fn allow_header(&self) -> String {
+        let methods = if self.allowed_methods.is_empty() {
+            DEFAULT_ALLOWED_METHODS
+        } else {
+            self.allowed_methods
+        };
+        methods
+            .iter()
+            .filter(|method| is_servable_method(method))
+            .map(Method::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn max_query_length(&mut self, value: Option<usize>) -> &mut Self {
+        self.max_query_length = value;
+        self
+    }
+
+    /// 生成错误响应，使用`error_pages`中对应的内容作为响应体
+    fn error_response<F>(
+        &self,
+        res: HttpResponseBuilder,
+        status: StatusCode,
+    ) -> Result<Response<Body<F>>> {
+        let res = res.status(status);
+        match self.error_pages.get(&status) {
+            Some((data, content_type)) => {
+                let res = res
+                    .header(header::CONTENT_TYPE, content_type)
+                    .header(header::CONTENT_LENGTH, data.len());
+                if self.file_response_builder.is_head {
+                    res.body(Body::Empty)
+                } else {
+                    res.body(Body::Bytes(data.clone()))
+                }
+            }
+            None => res.body(Body::Empty),
+        }
+    }
+
     pub fn build<F: IntoFileAccess>(
         &self,
         result: ResolveResult<F>,
     ) -> Result<Response<Body<F::Output>>> {
+        let mut res = self.build_result(result)?;
+
+        if let Some(ref name) = self.request_id_header {
+            echo_request_id(
+                res.headers_mut(),
+                self.request_headers,
+                name,
+                self.generate_request_id,
+            );
+        }
+        merge_headers(
+            res.headers_mut(),
+            &self.extra_headers,
+            self.override_headers,
+        );
+        Ok(res)
+    }
+
+    fn build_result<F: IntoFileAccess>(
+        &self,
+        result: ResolveResult<F>,
+    ) -> Result<Response<Body<F::Output>>> {
+        if let (Some(query), Some(max)) = (self.query, self.max_query_length) {
+            if query.len() > max {
+                return self.error_response(HttpResponseBuilder::new(), StatusCode::BAD_REQUEST);
+            }
+        }
+
         match result {
-            ResolveResult::MethodNotMatched => HttpResponseBuilder::new()
-                .status(StatusCode::BAD_REQUEST)
-                .body(Body::Empty),
-            ResolveResult::NotFound => HttpResponseBuilder::new().status(StatusCode::NOT_FOUND).body(Body::Empty),
-            ResolveResult::PermissionDenied => HttpResponseBuilder::new().status(StatusCode::FORBIDDEN).body(Body::Empty),
-            ResolveResult::IsDirectory { redirect_to: mut target } => {
-                if let Some(query) = self.query {
-                    target.push('?');
-                    target.push_str(query);
+            ResolveResult::MethodNotMatched => self.error_response(
+                HttpResponseBuilder::new().header(header::ALLOW, self.allow_header()),
+                if self.method_not_implemented {
+                    StatusCode::NOT_IMPLEMENTED
+                } else {
+                    StatusCode::METHOD_NOT_ALLOWED
+                },
+            ),
+            ResolveResult::BadRequest => {
+                self.error_response(HttpResponseBuilder::new(), StatusCode::BAD_REQUEST)
+            }
+            ResolveResult::NotFound => match self.default_favicon {
+                Some((ref data, ref content_type)) if self.path == "/favicon.ico" => {
+                    let res = HttpResponseBuilder::new()
+                        .status(StatusCode::OK)
+                        .header(header::CONTENT_TYPE, content_type)
+                        .header(header::CONTENT_LENGTH, data.len());
+                    if self.file_response_builder.is_head {
+                        res.body(Body::Empty)
+                    } else {
+                        res.body(Body::Bytes(data.clone()))
+                    }
+                }
+                _ => self.error_response(HttpResponseBuilder::new(), StatusCode::NOT_FOUND),
+            },
+            ResolveResult::PermissionDenied => {
+                self.error_response(HttpResponseBuilder::new(), StatusCode::FORBIDDEN)
+            }
+            ResolveResult::Unavailable => {
+                self.error_response(HttpResponseBuilder::new(), StatusCode::SERVICE_UNAVAILABLE)
+            }
+            ResolveResult::IsDirectory { redirect_to: mut target }
+            | ResolveResult::Redirect { redirect_to: mut target } => {
+                if let Some(query) = self.query.filter(|query| !query.is_empty()) {
+                    target = append_query(&target, query);
                 }
                 HttpResponseBuilder::new().status(StatusCode::MOVED_PERMANENTLY).header(header::LOCATION, target).body(Body::Empty)
             }
-            ResolveResult::Found(file) => self.file_response_builder.build(file),
+            ResolveResult::Directory { entries } => {
+                let (listing, content_type) = match self.autoindex_json {
+                    Some(ref content_type) => {
+                        (render_autoindex_json(&entries), content_type.as_str())
+                    }
+                    None => (
+                        render_autoindex(self.path, &entries),
+                        "text/html; charset=utf-8",
+                    ),
+                };
+                let res = HttpResponseBuilder::new()
+                    .status(StatusCode::OK)
+                    .header(header::CONTENT_TYPE, content_type)
+                    .header(header::CONTENT_LENGTH, listing.len());
+                if self.file_response_builder.is_head {
+                    res.body(Body::Empty)
+                } else {
+                    res.body(Body::Bytes(listing.into()))
+                }
+            }
+            ResolveResult::OptionsAllowed { accept_ranges } => {
+                let allow = self.allow_header();
+                // 预检请求同样需要CORS响应头
+                let mut res = self
+                    .file_response_builder
+                    .cors_headers(HttpResponseBuilder::new());
+                if let Some(cors) = self.file_response_builder.cors.as_ref() {
+                    if cors.varies_by_origin() {
+                        res = res.header(header::VARY, header::ORIGIN.as_str());
+                    }
+                }
+                if accept_ranges {
+                    res = res.header(header::ACCEPT_RANGES, "bytes");
+                }
+                res.status(StatusCode::NO_CONTENT)
+                    .header(header::ALLOW, allow)
+                    .body(Body::Empty)
+            }
+            ResolveResult::Found(file) => match file.status {
+                // 返回的不是资源本身的表示，条件请求和范围请求都不适用
+                Some(status) => {
+                    let mut builder = self.file_response_builder.clone();
+                    builder.if_match = None;
+                    builder.if_none_match = None;
+                    builder.if_modified_since = None;
+                    builder.if_range = None;
+                    builder.range = None;
+                    let mut res = builder.build(file)?;
+                    *res.status_mut() = status;
+                    Ok(res)
+                }
+                None => self.file_response_builder.build(file),
+            },
+        }
+    }
+}
+
+/// 在重定向地址后原样追加请求的查询字符串（客户端已经编码过）
+/// 地址中已有查询字符串时用`&`连接，片段始终保留在最后
+fn append_query(target: &str, query: &str) -> String {
+    let (base, fragment) = match target.find('#') {
+        Some(index) => target.split_at(index),
+        None => (target, ""),
+    };
+    let separator = if base.contains('?') { '&' } else { '?' };
+    format!("{base}{separator}{query}{fragment}")
+}
+
+/// 将`extra`中的头部合并到`headers`，`overwrite`为false时保留已有的同名头部
+pub(crate) fn merge_headers(headers: &mut HeaderMap, extra: &HeaderMap, overwrite: bool) {
+    for name in extra.keys() {
+        if headers.contains_key(name) {
+            if !overwrite {
+                continue;
+            }
+            headers.remove(name);
         }
+        for value in extra.get_all(name) {
+            headers.append(name.clone(), value.clone());
+        }
+    }
+}
+
+/// 将请求中的请求ID回显到响应，请求中没有且`generate`为true时生成一个
+pub(crate) fn echo_request_id(
+    headers: &mut HeaderMap,
+    request_headers: Option<&HeaderMap>,
+    name: &HeaderName,
+    generate: bool,
+) {
+    let request_id = request_headers
+        .and_then(|headers| headers.get(name))
+        .cloned()
+        .or_else(|| generate.then(generate_request_id));
+    if let Some(request_id) = request_id {
+        headers.insert(name.clone(), request_id);
     }
 }
+
+/// 生成UUID v4格式的随机请求ID
+fn generate_request_id() -> HeaderValue {
+    let mut bytes = [0u8; 16];
+    rand::rng().fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    let value = format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    );
+    HeaderValue::from_str(&value).unwrap()
+}
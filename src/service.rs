@@ -1,36 +1,97 @@
+use std::collections::HashMap;
 use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
 use std::path::PathBuf;
+#[cfg(feature = "tower")]
+use std::task::{Context, Poll};
 use std::{io::Error as IoError, pin::Pin};
 
-use http::{Request, Response};
+use http::{header, response::Builder as HttpResponseBuilder};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Request, Response, StatusCode};
+use hyper::body::Bytes;
 use hyper::service::Service;
+use mime_guess::Mime;
 
+use crate::response_builder::{echo_request_id, merge_headers};
+#[cfg(feature = "compression")]
+use crate::util::CompressionLoad;
+use crate::util::{BodyWrapper, ByteBudget, CacheControl, ContentDisposition, Cors};
 use crate::vfs::MemoryFs;
 use crate::{
     vfs::{FileOpener, IntoFileAccess, TokioFileOpener},
-    AcceptEncoding, Body, Resolver, ResponseBuilder,
+    AcceptEncoding, Body, Encoding, Resolver, ResponseBuilder, TrailingDots, TrailingSlash,
 };
 
 pub struct Static<O = TokioFileOpener> {
     pub resolver: Resolver<O>,
     pub cache_headers: Option<u32>,
+    /// 完整的Cache-Control配置，优先于`cache_headers`
+    pub cache_control: Option<CacheControl>,
+    /// 未设置`cache_headers`时使用的Cache-Control
+    pub default_cache_control: Option<String>,
+    /// 以附件或内联的方式输出文件
+    pub content_disposition: Option<ContentDisposition>,
+    /// 在文件内容前后追加固定内容
+    pub body_wrapper: Option<BodyWrapper>,
+    /// 为文件响应添加CORS响应头
+    pub cors: Option<Cors>,
+    /// 所有响应流共享的字节预算
+    pub byte_budget: Option<ByteBudget>,
+    /// 文件响应体每一帧的大小，较大的值可以减少大文件的帧数
+    pub chunk_size: Option<usize>,
+    /// 范围请求最多输出的范围数，为None时使用默认值
+    pub max_ranges: Option<usize>,
+    /// `/favicon.ico`不存在时返回的内容及其Content-Type
+    pub default_favicon: Option<(Bytes, String)>,
+    /// 错误响应的自定义内容及其Content-Type，按状态码区分
+    pub error_pages: Arc<HashMap<StatusCode, (Bytes, String)>>,
+    /// 为Some时目录列表输出为JSON，值为其Content-Type
+    pub autoindex_json: Option<String>,
+    /// 不允许的方法返回501而不是405
+    pub method_not_implemented: bool,
+    /// 查询字符串的最大长度，超出时返回400
+    pub max_query_length: Option<usize>,
+    /// 在响应中回显的请求ID头
+    pub request_id_header: Option<HeaderName>,
+    /// 请求中没有请求ID时生成一个
+    pub generate_request_id: bool,
+    /// 在响应的`Extensions`中放入实际输出的文件
+    pub served_file_extension: bool,
+    /// 追加到所有响应中的头部
+    pub extra_headers: Arc<HeaderMap>,
+    /// 额外的头部覆盖响应中已有的同名头部
+    pub override_headers: bool,
+    /// 维护模式，设置后所有请求都返回这里的状态码、内容及其Content-Type
+    pub maintenance: Option<(StatusCode, Bytes, Mime)>,
+    /// 完整响应以trailer输出`Content-Digest`和`Digest`，只对带`TE: trailers`的请求生效
+    #[cfg(feature = "digest")]
+    pub digest_trailer: bool,
+    /// 动态zstd压缩的级别
+    #[cfg(feature = "zstd")]
+    pub zstd_level: i32,
+    /// 按同时进行中的动态压缩数降低压缩级别或不再压缩
+    #[cfg(feature = "compression")]
+    pub compression_load: Option<CompressionLoad>,
 }
 
 impl Static<TokioFileOpener> {
     pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self::with_opener(TokioFileOpener::new(root))
+    }
+
+    /// 所有请求都返回同一个文件，参见`Resolver::single_file`
+    pub fn single_file(path: impl Into<PathBuf>) -> Self {
         Self {
-            resolver: Resolver::new(root),
-            cache_headers: None,
+            resolver: Resolver::single_file(path),
+            ..Self::new(PathBuf::new())
         }
     }
 }
 
 impl Static<MemoryFs> {
     pub fn from_memory_fs(fs: impl Into<MemoryFs>) -> Self {
-        Self {
-            resolver: Resolver::from_memory_fs(fs),
-            cache_headers: None,
-        }
+        Self::with_opener(fs.into())
     }
 }
 
@@ -39,6 +100,31 @@ impl<O: FileOpener> Static<O> {
         Self {
             resolver: Resolver::with_opener(opener),
             cache_headers: None,
+            cache_control: None,
+            default_cache_control: None,
+            content_disposition: None,
+            body_wrapper: None,
+            cors: None,
+            byte_budget: None,
+            chunk_size: None,
+            max_ranges: None,
+            default_favicon: None,
+            error_pages: Arc::default(),
+            autoindex_json: None,
+            method_not_implemented: false,
+            max_query_length: None,
+            request_id_header: None,
+            generate_request_id: false,
+            served_file_extension: false,
+            extra_headers: Arc::default(),
+            override_headers: false,
+            maintenance: None,
+            #[cfg(feature = "digest")]
+            digest_trailer: false,
+            #[cfg(feature = "zstd")]
+            zstd_level: 0,
+            #[cfg(feature = "compression")]
+            compression_load: None,
         }
     }
 
@@ -47,11 +133,240 @@ impl<O: FileOpener> Static<O> {
         self
     }
 
+    pub fn cache_control(&mut self, value: Option<CacheControl>) -> &mut Self {
+        self.cache_control = value;
+        self
+    }
+
+    pub fn default_cache_control(&mut self, value: Option<String>) -> &mut Self {
+        self.default_cache_control = value;
+        self
+    }
+
+    pub fn content_disposition(&mut self, value: Option<ContentDisposition>) -> &mut Self {
+        self.content_disposition = value;
+        self
+    }
+
+    pub fn body_wrapper(&mut self, value: Option<BodyWrapper>) -> &mut Self {
+        self.body_wrapper = value;
+        self
+    }
+
+    pub fn cors(&mut self, value: Option<Cors>) -> &mut Self {
+        self.cors = value;
+        self
+    }
+
+    pub fn byte_budget(&mut self, value: Option<ByteBudget>) -> &mut Self {
+        self.byte_budget = value;
+        self
+    }
+
+    pub fn chunk_size(&mut self, value: Option<usize>) -> &mut Self {
+        self.chunk_size = value;
+        self
+    }
+
+    pub fn max_ranges(&mut self, value: Option<usize>) -> &mut Self {
+        self.max_ranges = value;
+        self
+    }
+
+    #[cfg(feature = "digest")]
+    pub fn digest_trailer(&mut self, value: bool) -> &mut Self {
+        self.digest_trailer = value;
+        self
+    }
+
+    pub fn default_favicon(&mut self, data: Bytes, content_type: impl Into<String>) -> &mut Self {
+        self.default_favicon = Some((data, content_type.into()));
+        self
+    }
+
+    /// 设置错误响应的内容，如自定义的404页面
+    pub fn error_page(
+        &mut self,
+        status: StatusCode,
+        data: Bytes,
+        content_type: impl Into<String>,
+    ) -> &mut Self {
+        Arc::make_mut(&mut self.error_pages).insert(status, (data, content_type.into()));
+        self
+    }
+
+    /// 添加一个追加到所有响应中的头部，可以多次添加同名头部
+    pub fn extra_header(&mut self, name: HeaderName, value: HeaderValue) -> &mut Self {
+        Arc::make_mut(&mut self.extra_headers).append(name, value);
+        self
+    }
+
+    pub fn override_headers(&mut self, value: bool) -> &mut Self {
+        self.override_headers = value;
+        self
+    }
+
+    pub fn method_not_implemented(&mut self, value: bool) -> &mut Self {
+        self.method_not_implemented = value;
+        self
+    }
+
+    pub fn max_query_length(&mut self, value: Option<usize>) -> &mut Self {
+        self.max_query_length = value;
+        self
+    }
+
+    /// 回显请求中的请求ID头，`generate`为true时为没有请求ID的请求生成一个
+    pub fn request_id_header(&mut self, name: Option<HeaderName>, generate: bool) -> &mut Self {
+        self.request_id_header = name;
+        self.generate_request_id = generate;
+        self
+    }
+
+    pub fn served_file_extension(&mut self, value: bool) -> &mut Self {
+        self.served_file_extension = value;
+        self
+    }
+
+    /// 开启或关闭维护模式，开启时不再解析请求路径
+    pub fn maintenance(&mut self, value: Option<(StatusCode, Bytes, Mime)>) -> &mut Self {
+        self.maintenance = value;
+        self
+    }
+
+    pub fn trailing_slash(&mut self, value: TrailingSlash) -> &mut Self {
+        self.resolver.trailing_slash = value;
+        self
+    }
+
+    /// 为false时等同于`TrailingSlash::NoRedirect`
+    pub fn normalize_trailing_slash(&mut self, value: bool) -> &mut Self {
+        self.resolver.trailing_slash = if value {
+            TrailingSlash::AddToDirectories
+        } else {
+            TrailingSlash::NoRedirect
+        };
+        self
+    }
+
+    /// 路径片段末尾的`.`和空格的处理方式
+    pub fn trailing_dots(&mut self, value: TrailingDots) -> &mut Self {
+        self.resolver.trailing_dots = value;
+        self
+    }
+
+    pub fn clean_urls(&mut self, value: bool) -> &mut Self {
+        self.resolver.clean_urls = value;
+        self
+    }
+
+    /// 请求路径的大小写与磁盘上不一致时重定向，用于不区分大小写的文件系统
+    pub fn canonical_case_redirect(&mut self, value: bool) -> &mut Self {
+        self.resolver.canonical_case_redirect = value;
+        self
+    }
+
+    pub fn index_files(&mut self, index_files: Vec<String>) -> &mut Self {
+        self.resolver.index_files = index_files;
+        self
+    }
+
+    pub fn max_depth(&mut self, value: Option<usize>) -> &mut Self {
+        self.resolver.max_depth = value;
+        self
+    }
+
+    pub fn serve_dotfiles(&mut self, value: bool) -> &mut Self {
+        self.resolver.serve_dotfiles = value;
+        self
+    }
+
+    pub fn spa_fallback(&mut self, path: &str) -> &mut Self {
+        self.resolver.set_spa_fallback(path);
+        self
+    }
+
+    /// 返回SPA回退文件时的状态码，如404，HEAD请求同样只返回响应头
+    pub fn spa_fallback_status(&mut self, status: StatusCode) -> &mut Self {
+        self.resolver.spa_fallback_status = status;
+        self
+    }
+
+    #[cfg(feature = "digest")]
+    pub fn strong_etag(&mut self, value: bool) -> &mut Self {
+        self.resolver.strong_etag = value;
+        self
+    }
+
+    pub fn resolve_timeout(&mut self, value: Option<Duration>) -> &mut Self {
+        self.resolver.resolve_timeout = value;
+        self
+    }
+
+    pub fn autoindex(&mut self, value: bool) -> &mut Self {
+        self.resolver.autoindex = value;
+        self
+    }
+
+    /// 目录列表输出为JSON并使用该Content-Type，如`application/json`，为None时输出HTML
+    pub fn autoindex_json(&mut self, content_type: Option<String>) -> &mut Self {
+        self.autoindex_json = content_type;
+        self
+    }
+
+    pub fn noindex_marker(&mut self, value: Option<String>) -> &mut Self {
+        self.resolver.noindex_marker = value;
+        self
+    }
+
+    /// 只有GET、HEAD和OPTIONS能够处理，其它方法会被忽略
+    pub fn allowed_methods(&mut self, allowed_methods: Vec<Method>) -> &mut Self {
+        self.resolver.set_allowed_methods(allowed_methods);
+        self
+    }
+
     pub fn allowed_encodings(&mut self, allowed_encodings: AcceptEncoding) -> &mut Self {
         self.resolver.allowed_encodings = allowed_encodings;
         self
     }
 
+    pub fn charset(&mut self, value: Option<String>) -> &mut Self {
+        self.resolver.charset = value;
+        self
+    }
+
+    pub fn default_content_type(&mut self, value: Option<String>) -> &mut Self {
+        self.resolver.default_content_type = value;
+        self
+    }
+
+    pub fn force_encoding(&mut self, encoding: Option<Encoding>) -> &mut Self {
+        self.resolver.force_encoding = encoding;
+        self
+    }
+
+    /// 没有预压缩文件时动态压缩，编码同时需要在`allowed_encodings`中允许
+    #[cfg(feature = "compression")]
+    pub fn compress_on_the_fly(&mut self, encodings: AcceptEncoding) -> &mut Self {
+        self.resolver.compress_on_the_fly = encodings;
+        self
+    }
+
+    /// 动态zstd压缩的级别，为0时使用zstd的默认级别
+    #[cfg(feature = "zstd")]
+    pub fn zstd_level(&mut self, value: i32) -> &mut Self {
+        self.zstd_level = value;
+        self
+    }
+
+    /// 共享的动态压缩负载，进行中的压缩较多时使用最快的级别或直接输出原文件
+    /// 多个`Static`可以共享同一个`CompressionLoad`
+    #[cfg(feature = "compression")]
+    pub fn compression_load(&mut self, value: Option<CompressionLoad>) -> &mut Self {
+        self.compression_load = value;
+        self
+    }
+
     pub async fn serve<B>(
         self,
         request: Request<B>,
@@ -59,14 +374,102 @@ impl<O: FileOpener> Static<O> {
         let Self {
             resolver,
             cache_headers,
+            cache_control,
+            default_cache_control,
+            content_disposition,
+            body_wrapper,
+            cors,
+            byte_budget,
+            chunk_size,
+            max_ranges,
+            default_favicon,
+            error_pages,
+            autoindex_json,
+            method_not_implemented,
+            max_query_length,
+            request_id_header,
+            generate_request_id,
+            served_file_extension,
+            extra_headers,
+            override_headers,
+            maintenance,
+            #[cfg(feature = "digest")]
+            digest_trailer,
+            #[cfg(feature = "zstd")]
+            zstd_level,
+            #[cfg(feature = "compression")]
+            compression_load,
         } = self;
-        resolver.resovle_request(&request).await.map(|result| {
-            ResponseBuilder::new()
+
+        if let Some((status, data, content_type)) = maintenance {
+            let res = HttpResponseBuilder::new()
+                .status(status)
+                .header(header::CONTENT_TYPE, content_type.as_ref())
+                .header(header::CONTENT_LENGTH, data.len());
+            let body = if request.method() == Method::HEAD {
+                Body::Empty
+            } else {
+                Body::Bytes(data)
+            };
+            let mut res = res.body(body).expect("unable to build response");
+            if let Some(ref name) = request_id_header {
+                echo_request_id(
+                    res.headers_mut(),
+                    Some(request.headers()),
+                    name,
+                    generate_request_id,
+                );
+            }
+            merge_headers(res.headers_mut(), &extra_headers, override_headers);
+            return Ok(res);
+        }
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "serve",
+            method = %request.method(),
+            path = request.uri().path(),
+            status = tracing::field::Empty,
+        );
+        let resolve = resolver.resolve_request(&request);
+        #[cfg(feature = "tracing")]
+        let resolve = tracing::Instrument::instrument(resolve, span.clone());
+        let response = resolve.await.map(|result| {
+            let mut builder = ResponseBuilder::new();
+            builder
                 .request(&request)
+                .allowed_methods(&resolver.allowed_methods)
+                .method_not_implemented(method_not_implemented)
                 .cache_headers(cache_headers)
-                .build(result)
-                .expect("unable to build response")
-        })
+                .cache_control(cache_control)
+                .default_cache_control(default_cache_control)
+                .content_disposition(content_disposition)
+                .body_wrapper(body_wrapper)
+                .cors(cors)
+                .byte_budget(byte_budget)
+                .chunk_size(chunk_size)
+                .max_ranges(max_ranges)
+                .default_favicon(default_favicon)
+                .error_pages(error_pages)
+                .autoindex_json(autoindex_json)
+                .max_query_length(max_query_length)
+                .request_id_header(request_id_header)
+                .generate_request_id(generate_request_id)
+                .served_file_extension(served_file_extension)
+                .extra_headers(extra_headers)
+                .override_headers(override_headers);
+            #[cfg(feature = "digest")]
+            builder.digest_trailer(digest_trailer);
+            #[cfg(feature = "zstd")]
+            builder.zstd_level(zstd_level);
+            #[cfg(feature = "compression")]
+            builder.compression_load(compression_load);
+            builder.build(result).expect("unable to build response")
+        });
+        #[cfg(feature = "tracing")]
+        if let Ok(ref response) = response {
+            span.record("status", response.status().as_u16());
+        }
+        response
     }
 }
 
@@ -75,6 +478,31 @@ impl<O> Clone for Static<O> {
         Self {
             resolver: self.resolver.clone(),
             cache_headers: self.cache_headers,
+            cache_control: self.cache_control.clone(),
+            default_cache_control: self.default_cache_control.clone(),
+            content_disposition: self.content_disposition,
+            body_wrapper: self.body_wrapper.clone(),
+            cors: self.cors.clone(),
+            byte_budget: self.byte_budget.clone(),
+            chunk_size: self.chunk_size,
+            max_ranges: self.max_ranges,
+            default_favicon: self.default_favicon.clone(),
+            error_pages: self.error_pages.clone(),
+            autoindex_json: self.autoindex_json.clone(),
+            method_not_implemented: self.method_not_implemented,
+            max_query_length: self.max_query_length,
+            request_id_header: self.request_id_header.clone(),
+            generate_request_id: self.generate_request_id,
+            served_file_extension: self.served_file_extension,
+            extra_headers: self.extra_headers.clone(),
+            override_headers: self.override_headers,
+            maintenance: self.maintenance.clone(),
+            #[cfg(feature = "digest")]
+            digest_trailer: self.digest_trailer,
+            #[cfg(feature = "zstd")]
+            zstd_level: self.zstd_level,
+            #[cfg(feature = "compression")]
+            compression_load: self.compression_load.clone(),
         }
     }
 }
@@ -93,3 +521,24 @@ where
         Box::pin(self.clone().serve(request))
     }
 }
+
+/// 用于`tower::ServiceBuilder`等中间件组合，与hyper的`Service`行为相同
+#[cfg(feature = "tower")]
+impl<O, B> tower_service::Service<Request<B>> for Static<O>
+where
+    O: FileOpener,
+    B: Send + Sync + 'static,
+{
+    type Response = Response<Body<<O::File as IntoFileAccess>::Output>>;
+    type Error = IoError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    /// 不需要等待任何资源，始终就绪
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        Box::pin(self.clone().serve(request))
+    }
+}
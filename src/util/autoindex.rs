@@ -0,0 +1,126 @@
+/// 生成目录索引页面
+use std::fmt::Write;
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::vfs::DirectoryEntry;
+
+/// 链接中的一段路径，只保留不需要编码的字符
+pub(crate) const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// 转义HTML中的特殊字符
+fn escape_html(s: &str) -> String {
+    let mut buf = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => buf.push_str("&amp;"),
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            '"' => buf.push_str("&quot;"),
+            '\'' => buf.push_str("&#39;"),
+            _ => buf.push(c),
+        }
+    }
+    buf
+}
+
+/// 转义JSON字符串中的特殊字符
+fn escape_json(s: &str) -> String {
+    let mut buf = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            c if c < ' ' => write!(&mut buf, "\\u{:04x}", c as u32).expect("buffer write failed"),
+            _ => buf.push(c),
+        }
+    }
+    buf
+}
+
+/// 目录排在前面，同类按名称排序
+fn sorted_entries(entries: &[DirectoryEntry]) -> Vec<&DirectoryEntry> {
+    let mut entries: Vec<_> = entries.iter().collect();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    entries
+}
+
+/// 渲染`request_path`目录的HTML列表，目录排在前面，同类按名称排序
+pub(crate) fn render_autoindex(request_path: &str, entries: &[DirectoryEntry]) -> String {
+    let entries = sorted_entries(entries);
+
+    // 请求路径本身已经是编码后的形式，直接作为链接前缀
+    let base = escape_html(request_path.trim_end_matches('/'));
+    let title = escape_html(&percent_decode_str(request_path).decode_utf8_lossy());
+
+    let mut buf = String::with_capacity(256 + entries.len() * 128);
+    write!(
+        &mut buf,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Index of {title}</title>\n</head>\n<body>\n<h1>Index of {title}</h1>\n<table>\n<tr><th>Name</th><th>Size</th><th>Modified</th></tr>\n"
+    )
+    .expect("buffer write failed");
+
+    // 使用绝对地址，请求路径缺少末尾斜杠时链接同样正确
+    if let Some(index) = base.rfind('/') {
+        writeln!(
+            &mut buf,
+            "<tr><td><a href=\"{}/\">../</a></td><td></td><td></td></tr>",
+            &base[..index]
+        )
+        .expect("buffer write failed");
+    }
+
+    for entry in entries {
+        let suffix = if entry.is_dir { "/" } else { "" };
+        let size = if entry.is_dir {
+            "-".to_string()
+        } else {
+            entry.size.to_string()
+        };
+        let modified = entry
+            .modified
+            .map(httpdate::fmt_http_date)
+            .unwrap_or_default();
+        writeln!(
+            &mut buf,
+            "<tr><td><a href=\"{base}/{href}{suffix}\">{name}{suffix}</a></td><td>{size}</td><td>{modified}</td></tr>",
+            href = utf8_percent_encode(&entry.name, PATH_SEGMENT),
+            name = escape_html(&entry.name),
+        )
+        .expect("buffer write failed");
+    }
+
+    buf.push_str("</table>\n</body>\n</html>\n");
+    buf
+}
+
+/// 渲染目录的JSON列表，顺序与HTML列表相同
+/// 每个条目为`{"name":..,"is_dir":..,"size":..,"modified":..}`，`modified`为HTTP日期或null
+pub(crate) fn render_autoindex_json(entries: &[DirectoryEntry]) -> String {
+    let entries = sorted_entries(entries);
+    let mut buf = String::with_capacity(2 + entries.len() * 96);
+    buf.push('[');
+    for (i, entry) in entries.into_iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        let modified = entry
+            .modified
+            .map(|modified| format!("\"{}\"", httpdate::fmt_http_date(modified)))
+            .unwrap_or_else(|| "null".to_string());
+        write!(
+            &mut buf,
+            "{{\"name\":\"{}\",\"is_dir\":{},\"size\":{},\"modified\":{modified}}}",
+            escape_json(&entry.name),
+            entry.is_dir,
+            entry.size,
+        )
+        .expect("buffer write failed");
+    }
+    buf.push(']');
+    buf
+}
@@ -0,0 +1,113 @@
+/// 全局字节预算，限制所有响应流同时在途的字节数
+use std::{
+    cmp::min,
+    fmt,
+    io::Error as IoError,
+    mem,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures_util::future::BoxFuture;
+use tokio::sync::{AcquireError, OwnedSemaphorePermit, Semaphore};
+
+/// 单次申请额度的最大字节数
+const MAX_CHUNK_SIZE: usize = 8 * 1024;
+
+/// 字节预算
+/// 共享同一个`ByteBudget`的流在读取每一块数据之前都要先申请额度，
+/// 数据块被消费（即下一次poll）时归还额度，预算耗尽时其余的流会等待
+#[derive(Clone)]
+pub struct ByteBudget {
+    semaphore: Arc<Semaphore>,
+    chunk_size: usize,
+}
+
+impl ByteBudget {
+    pub fn new(total_bytes: usize) -> Self {
+        let total_bytes = total_bytes.clamp(1, Semaphore::MAX_PERMITS);
+        Self {
+            semaphore: Arc::new(Semaphore::new(total_bytes)),
+            chunk_size: min(total_bytes, MAX_CHUNK_SIZE),
+        }
+    }
+
+    /// 当前剩余的字节额度
+    pub fn available_bytes(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// 每块数据申请的字节数，同时也是单次读取的上限
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    fn acquire(&self) -> BoxFuture<'static, Result<OwnedSemaphorePermit, AcquireError>> {
+        Box::pin(
+            self.semaphore
+                .clone()
+                .acquire_many_owned(self.chunk_size as u32),
+        )
+    }
+}
+
+impl fmt::Debug for ByteBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteBudget")
+            .field("available_bytes", &self.available_bytes())
+            .field("chunk_size", &self.chunk_size)
+            .finish()
+    }
+}
+
+/// 流持有额度的状态
+#[derive(Default)]
+pub(crate) enum BudgetState {
+    #[default]
+    Idle,
+    Acquiring(BoxFuture<'static, Result<OwnedSemaphorePermit, AcquireError>>),
+    /// 已拿到额度，正在读取
+    Ready(OwnedSemaphorePermit),
+    /// 额度对应的数据块已经输出，等待被消费
+    Emitted {
+        _permit: OwnedSemaphorePermit,
+    },
+}
+
+impl BudgetState {
+    /// 申请读取下一块数据的额度，上一块数据的额度会先归还
+    pub(crate) fn poll_acquire(
+        &mut self,
+        budget: &ByteBudget,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), IoError>> {
+        loop {
+            match self {
+                BudgetState::Idle | BudgetState::Emitted { .. } => {
+                    *self = BudgetState::Acquiring(budget.acquire());
+                }
+                BudgetState::Acquiring(fut) => match fut.as_mut().poll(cx) {
+                    Poll::Ready(Ok(permit)) => *self = BudgetState::Ready(permit),
+                    Poll::Ready(Err(e)) => {
+                        *self = BudgetState::Idle;
+                        return Poll::Ready(Err(IoError::other(e)));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+                BudgetState::Ready(_) => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+
+    /// 数据块已输出，额度保留到下一次poll
+    pub(crate) fn mark_emitted(&mut self) {
+        if let BudgetState::Ready(permit) = mem::take(self) {
+            *self = BudgetState::Emitted { _permit: permit };
+        }
+    }
+
+    /// 归还额度
+    pub(crate) fn release(&mut self) {
+        *self = BudgetState::Idle;
+    }
+}
@@ -0,0 +1,86 @@
+/// 生成`Cache-Control`响应头
+use std::fmt;
+
+/// Cache-Control配置
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    /// 只允许浏览器缓存，输出`private`而不是`public`
+    pub private: bool,
+    /// 每次使用缓存前都要重新验证
+    pub no_cache: bool,
+    pub max_age: Option<u32>,
+    /// 过期后仍可先返回旧内容、同时后台重新验证的秒数
+    pub stale_while_revalidate: Option<u32>,
+    /// 源站出错时仍可返回旧内容的秒数
+    pub stale_if_error: Option<u32>,
+    /// 禁止缓存，同时不再输出ETag和Last-Modified，其余选项被忽略
+    pub no_store: bool,
+    /// 内容在有效期内不会变化，浏览器刷新时也不必重新验证
+    pub immutable: bool,
+}
+
+impl CacheControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn private(&mut self, value: bool) -> &mut Self {
+        self.private = value;
+        self
+    }
+
+    pub fn no_cache(&mut self, value: bool) -> &mut Self {
+        self.no_cache = value;
+        self
+    }
+
+    pub fn max_age(&mut self, seconds: u32) -> &mut Self {
+        self.max_age = Some(seconds);
+        self
+    }
+
+    pub fn stale_while_revalidate(&mut self, seconds: u32) -> &mut Self {
+        self.stale_while_revalidate = Some(seconds);
+        self
+    }
+
+    pub fn stale_if_error(&mut self, seconds: u32) -> &mut Self {
+        self.stale_if_error = Some(seconds);
+        self
+    }
+
+    pub fn no_store(&mut self, value: bool) -> &mut Self {
+        self.no_store = value;
+        self
+    }
+
+    pub fn immutable(&mut self, value: bool) -> &mut Self {
+        self.immutable = value;
+        self
+    }
+}
+
+impl fmt::Display for CacheControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.no_store {
+            return f.write_str("no-store");
+        }
+        f.write_str(if self.private { "private" } else { "public" })?;
+        if self.no_cache {
+            f.write_str(", no-cache")?;
+        }
+        if let Some(seconds) = self.max_age {
+            write!(f, ", max-age={}", seconds)?;
+        }
+        if let Some(seconds) = self.stale_while_revalidate {
+            write!(f, ", stale-while-revalidate={}", seconds)?;
+        }
+        if let Some(seconds) = self.stale_if_error {
+            write!(f, ", stale-if-error={}", seconds)?;
+        }
+        if self.immutable {
+            f.write_str(", immutable")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+/// 动态压缩的负载，负载较高时降低压缩级别或不再压缩
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// 动态压缩的质量
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompressionQuality {
+    /// 使用配置的压缩级别
+    Default,
+    /// 使用最快的压缩级别
+    Fast,
+    /// 不压缩，直接输出原文件
+    Off,
+}
+
+/// 同时进行中的动态压缩数
+/// 共享同一个`CompressionLoad`的响应在开始压缩时按当前的压缩数选择质量，
+/// 压缩流被丢弃（输出完毕或连接断开）时压缩数减一
+#[derive(Clone, Debug)]
+pub struct CompressionLoad {
+    in_flight: Arc<AtomicUsize>,
+    /// 进行中的压缩数达到该值时使用最快的压缩级别
+    pub fast_at: usize,
+    /// 进行中的压缩数达到该值时不再压缩
+    pub off_at: usize,
+}
+
+impl CompressionLoad {
+    pub fn new(fast_at: usize, off_at: usize) -> Self {
+        Self {
+            in_flight: Arc::default(),
+            fast_at,
+            off_at,
+        }
+    }
+
+    /// 当前进行中的压缩数
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// 按当前的压缩数选择质量
+    pub fn quality(&self) -> CompressionQuality {
+        let in_flight = self.in_flight();
+        if in_flight >= self.off_at {
+            CompressionQuality::Off
+        } else if in_flight >= self.fast_at {
+            CompressionQuality::Fast
+        } else {
+            CompressionQuality::Default
+        }
+    }
+
+    /// 开始一次压缩，返回的guard被丢弃时结束
+    pub(crate) fn start(&self) -> InFlightGuard {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard {
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+/// 一次进行中的压缩
+pub(crate) struct InFlightGuard {
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,58 @@
+/// 生成`Content-Disposition`响应头
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// RFC 5987中`attr-char`以外的字符都需要编码
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// 响应内容的展示方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContentDisposition {
+    /// 作为附件下载
+    Attachment,
+    /// 在浏览器中直接展示
+    Inline,
+}
+
+impl ContentDisposition {
+    /// 生成带文件名的头部值，文件名包含非ASCII字符时额外输出`filename*`
+    pub fn to_header_value(self, filename: &str) -> String {
+        let disposition = match self {
+            ContentDisposition::Attachment => "attachment",
+            ContentDisposition::Inline => "inline",
+        };
+
+        let mut fallback = String::with_capacity(filename.len() + 2);
+        for c in filename.chars() {
+            match c {
+                '"' | '\\' => {
+                    fallback.push('\\');
+                    fallback.push(c);
+                }
+                // 控制字符和非ASCII字符不能出现在quoted-string中
+                ' '..='~' => fallback.push(c),
+                _ => fallback.push('_'),
+            }
+        }
+
+        if filename.is_ascii() && !filename.chars().any(|c| c.is_ascii_control()) {
+            format!("{disposition}; filename=\"{fallback}\"")
+        } else {
+            format!(
+                "{disposition}; filename=\"{fallback}\"; filename*=UTF-8''{}",
+                utf8_percent_encode(filename, ATTR_CHAR)
+            )
+        }
+    }
+}
@@ -0,0 +1,74 @@
+/// 生成跨域资源共享（CORS）响应头
+use http::Method;
+
+/// 允许跨域访问的来源
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CorsOrigin {
+    /// 允许任意来源，输出`*`
+    Any,
+    /// 只允许列表中的来源，匹配时回显请求的`Origin`
+    List(Vec<String>),
+}
+
+/// 跨域访问的配置
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cors {
+    pub origin: CorsOrigin,
+    /// `Access-Control-Allow-Methods`中的方法，为空时不输出
+    pub allow_methods: Vec<Method>,
+    /// `Access-Control-Max-Age`的秒数
+    pub max_age: Option<u32>,
+}
+
+impl Cors {
+    /// 允许任意来源
+    pub fn any() -> Self {
+        Self {
+            origin: CorsOrigin::Any,
+            allow_methods: Vec::new(),
+            max_age: None,
+        }
+    }
+
+    /// 只允许列表中的来源
+    pub fn allow_list<S: Into<String>>(origins: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            origin: CorsOrigin::List(origins.into_iter().map(Into::into).collect()),
+            ..Self::any()
+        }
+    }
+
+    pub fn allow_methods(&mut self, value: Vec<Method>) -> &mut Self {
+        self.allow_methods = value;
+        self
+    }
+
+    pub fn max_age(&mut self, value: Option<u32>) -> &mut Self {
+        self.max_age = value;
+        self
+    }
+
+    /// 根据请求的`Origin`得到`Access-Control-Allow-Origin`的值，不允许时返回None
+    pub fn allow_origin<'a>(&'a self, origin: Option<&'a str>) -> Option<&'a str> {
+        match self.origin {
+            CorsOrigin::Any => Some("*"),
+            CorsOrigin::List(ref list) => {
+                origin.filter(|origin| list.iter().any(|v| v.eq_ignore_ascii_case(origin)))
+            }
+        }
+    }
+
+    /// 响应是否随请求的`Origin`变化，共享缓存需要区分
+    pub fn varies_by_origin(&self) -> bool {
+        matches!(self.origin, CorsOrigin::List(_))
+    }
+
+    /// `Access-Control-Allow-Methods`的值
+    pub fn allow_methods_header(&self) -> Option<String> {
+        if self.allow_methods.is_empty() {
+            return None;
+        }
+        let methods: Vec<&str> = self.allow_methods.iter().map(Method::as_str).collect();
+        Some(methods.join(", "))
+    }
+}
@@ -4,12 +4,15 @@ use http_range::HttpRange;
 use hyper::body::Bytes;
 use std::{
     io::{Error as IoError, SeekFrom},
+    mem,
     pin::Pin,
     task::Poll,
     vec,
 };
 
 use crate::vfs::{FileAccess, TokioFileAccess};
+
+use super::{BudgetState, ByteBudget};
 use std::fmt::Write;
 
 /// 根据游标读取文件字节流
@@ -17,13 +20,21 @@ use std::fmt::Write;
 pub struct FileBytesStream<F = TokioFileAccess> {
     file: F,
     remaining: u64,
+    /// 是否由`new_with_limit`创建，此时`remaining`即为尚未输出的长度
+    limited: bool,
+    budget: Option<ByteBudget>,
+    budget_state: BudgetState,
+    /// 每次输出的数据块大小，为None时每次读取直接输出
+    chunk_size: Option<usize>,
+    /// 凑满`chunk_size`之前已经读到的数据
+    buffered: Vec<u8>,
 }
 
 impl<F> FileBytesStream<F> {
     pub fn new(file: F) -> Self {
         Self {
-            file,
-            remaining: u64::MAX,
+            limited: false,
+            ..Self::new_with_limit(file, u64::MAX)
         }
     }
 
@@ -31,8 +42,31 @@ impl<F> FileBytesStream<F> {
         Self {
             file,
             remaining: limit,
+            limited: true,
+            budget: None,
+            budget_state: BudgetState::Idle,
+            chunk_size: None,
+            buffered: Vec::new(),
         }
     }
+
+    /// 设置每次输出的数据块大小，多次读取的数据合并后再输出，减少响应体的帧数
+    /// 同时设置了字节预算时，数据块不超过预算的`chunk_size`
+    pub fn set_chunk_size(&mut self, chunk_size: Option<usize>) {
+        self.chunk_size = chunk_size.filter(|size| *size > 0);
+    }
+
+    /// 设置全局字节预算，每读取一块数据前先申请额度
+    pub fn set_byte_budget(&mut self, budget: Option<ByteBudget>) {
+        self.budget = budget;
+        self.budget_state.release();
+    }
+
+    /// 尚未输出的字节数，不限长度读取时未知
+    pub fn remaining(&self) -> Option<u64> {
+        self.limited
+            .then(|| self.remaining + self.buffered.len() as u64)
+    }
 }
 
 impl<F: FileAccess> Stream for FileBytesStream<F> {
@@ -44,19 +78,56 @@ impl<F: FileAccess> Stream for FileBytesStream<F> {
         let Self {
             ref mut file,
             ref mut remaining,
+            limited: _,
+            ref budget,
+            ref mut budget_state,
+            chunk_size,
+            ref mut buffered,
         } = *self;
 
-        match Pin::new(file).poll_read(cx, *remaining as usize) {
-            Poll::Ready(Ok(buf)) => {
-                *remaining -= buf.len() as u64;
-                if buf.is_empty() {
-                    Poll::Ready(None)
-                } else {
-                    Poll::Ready(Some(Ok(buf)))
+        // 已经读满，不再申请额度或发起长度为0的读取
+        if *remaining == 0 && buffered.is_empty() {
+            budget_state.release();
+            return Poll::Ready(None);
+        }
+
+        let mut target = chunk_size.unwrap_or(usize::MAX);
+        if let Some(budget) = budget {
+            match budget_state.poll_acquire(budget, cx) {
+                Poll::Ready(Ok(())) => target = target.min(budget.chunk_size()),
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        loop {
+            let len = (*remaining).min(target.saturating_sub(buffered.len()) as u64) as usize;
+            if len == 0 {
+                budget_state.mark_emitted();
+                return Poll::Ready(Some(Ok(mem::take(buffered).into())));
+            }
+
+            match Pin::new(&mut *file).poll_read(cx, len) {
+                Poll::Ready(Ok(buf)) if buf.is_empty() => {
+                    if !buffered.is_empty() {
+                        budget_state.mark_emitted();
+                        return Poll::Ready(Some(Ok(mem::take(buffered).into())));
+                    }
+                    budget_state.release();
+                    return Poll::Ready(None);
+                }
+                Poll::Ready(Ok(buf)) => {
+                    *remaining -= buf.len() as u64;
+                    if chunk_size.is_none() {
+                        budget_state.mark_emitted();
+                        return Poll::Ready(Some(Ok(buf)));
+                    }
+                    // 读取被挂起时保留已读到的数据，下次poll继续凑满
+                    buffered.extend_from_slice(&buf);
                 }
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e))),
+                Poll::Pending => return Poll::Pending,
             }
-            Poll::Ready(Err(e)) => Poll::Ready(Some(Err(e))),
-            Poll::Pending => Poll::Pending,
         }
     }
 }
@@ -84,6 +155,19 @@ impl<F> FileBytesStreamRange<F> {
         }
     }
 
+    pub fn set_byte_budget(&mut self, budget: Option<ByteBudget>) {
+        self.file_stream.set_byte_budget(budget);
+    }
+
+    pub fn set_chunk_size(&mut self, chunk_size: Option<usize>) {
+        self.file_stream.set_chunk_size(chunk_size);
+    }
+
+    /// 范围内尚未输出的字节数
+    pub fn remaining(&self) -> Option<u64> {
+        self.file_stream.remaining()
+    }
+
     fn without_initial_range(file: F) -> Self {
         Self {
             file_stream: FileBytesStream::new_with_limit(file, 0),
@@ -130,6 +214,16 @@ impl<F: FileAccess> Stream for FileBytesStreamRange<F> {
     }
 }
 
+/// 多段响应中各段的输出顺序
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RangeOrder {
+    /// 按请求中的顺序
+    #[default]
+    Request,
+    /// 按起始位置升序
+    Sorted,
+}
+
 /// 读取一个文件的多段，并自动组装响应体`boundary`
 pub struct FileBytesStreamMultiRange<F = TokioFileAccess> {
     file_range: FileBytesStreamRange<F>,
@@ -158,16 +252,39 @@ impl<F> FileBytesStreamMultiRange<F> {
         self.content_type = content_type.into();
     }
 
+    pub fn set_byte_budget(&mut self, budget: Option<ByteBudget>) {
+        self.file_range.set_byte_budget(budget);
+    }
+
+    pub fn set_chunk_size(&mut self, chunk_size: Option<usize>) {
+        self.file_range.set_chunk_size(chunk_size);
+    }
+
+    /// 设置尚未输出的各段的顺序
+    pub fn set_range_order(&mut self, order: RangeOrder) {
+        if order == RangeOrder::Sorted {
+            let mut ranges = self.range_iter.as_slice().to_vec();
+            ranges.sort_by_key(|range| range.start);
+            self.range_iter = ranges.into_iter();
+        }
+    }
+
+    /// 尚未输出的响应体长度，开始输出前即为完整的Content-Length
     pub fn compute_length(&self) -> u64 {
         let Self {
+            ref file_range,
             ref range_iter,
+            is_first_boundary,
+            completed,
             ref boundary,
             ref content_type,
             file_length,
-            ..
         } = *self;
-        let mut total_length = 0;
-        let mut is_first = true;
+        if completed {
+            return 0;
+        }
+        let mut total_length = file_range.remaining().unwrap_or(0);
+        let mut is_first = is_first_boundary;
         for range in range_iter.as_slice() {
             let header =
                 render_multipart_header(boundary, content_type, *range, is_first, file_length);
@@ -195,7 +312,7 @@ fn render_multipart_header(
     }
     write!(
         &mut buf,
-        "--{boundary}\r\nContent-Range: {}-{}/{file_length}\r\n",
+        "--{boundary}\r\nContent-Range: bytes {}-{}/{file_length}\r\n",
         range.start,
         range.start + range.length - 1
     )
@@ -0,0 +1,121 @@
+/// 边读取文件边压缩，用于没有预压缩文件时动态压缩
+use flate2::{write::GzEncoder, Compression};
+use futures_util::Stream;
+use hyper::body::Bytes;
+use std::{
+    io::{Error as IoError, Write},
+    mem,
+    pin::Pin,
+    task::{ready, Poll},
+};
+
+use crate::vfs::{FileAccess, TokioFileAccess};
+
+use super::{FileBytesStream, InFlightGuard};
+
+/// 压缩器，输出写入内部的缓冲区
+enum Encoder {
+    Gzip(GzEncoder<Vec<u8>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, Vec<u8>>),
+}
+
+impl Encoder {
+    /// 写入一块数据，返回目前已经产生的压缩数据
+    fn write(&mut self, buf: &[u8]) -> Result<Bytes, IoError> {
+        match self {
+            Encoder::Gzip(encoder) => {
+                encoder.write_all(buf)?;
+                Ok(mem::take(encoder.get_mut()).into())
+            }
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(encoder) => {
+                encoder.write_all(buf)?;
+                Ok(mem::take(encoder.get_mut()).into())
+            }
+        }
+    }
+
+    /// 结束压缩，返回剩余的压缩数据
+    fn finish(self) -> Result<Bytes, IoError> {
+        match self {
+            Encoder::Gzip(encoder) => encoder.finish().map(Into::into),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(encoder) => encoder.finish().map(Into::into),
+        }
+    }
+}
+
+/// 包装`FileBytesStream`，输出压缩后的数据，长度事先未知
+pub struct FileBytesStreamCompressed<F = TokioFileAccess> {
+    file_stream: FileBytesStream<F>,
+    /// 压缩结束后为None
+    encoder: Option<Encoder>,
+    /// 计入`CompressionLoad`的进行中压缩数，流被丢弃时释放
+    _in_flight: Option<InFlightGuard>,
+}
+
+impl<F> FileBytesStreamCompressed<F> {
+    pub fn gzip(file_stream: FileBytesStream<F>) -> Self {
+        Self::gzip_with_level(file_stream, Compression::default().level())
+    }
+
+    /// 使用指定级别的gzip压缩，`level`为0-9
+    pub fn gzip_with_level(file_stream: FileBytesStream<F>, level: u32) -> Self {
+        Self {
+            file_stream,
+            encoder: Some(Encoder::Gzip(GzEncoder::new(
+                Vec::new(),
+                Compression::new(level.min(9)),
+            ))),
+            _in_flight: None,
+        }
+    }
+
+    /// 使用zstd压缩，`level`为0时使用zstd的默认级别
+    #[cfg(feature = "zstd")]
+    pub fn zstd(file_stream: FileBytesStream<F>, level: i32) -> Result<Self, IoError> {
+        Ok(Self {
+            file_stream,
+            encoder: Some(Encoder::Zstd(zstd::stream::write::Encoder::new(
+                Vec::new(),
+                level,
+            )?)),
+            _in_flight: None,
+        })
+    }
+
+    pub(crate) fn set_in_flight(&mut self, guard: InFlightGuard) {
+        self._in_flight = Some(guard);
+    }
+}
+
+impl<F: FileAccess> Stream for FileBytesStreamCompressed<F> {
+    type Item = Result<Bytes, IoError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let Self {
+            ref mut file_stream,
+            ref mut encoder,
+            _in_flight: _,
+        } = *self;
+
+        loop {
+            let Some(current) = encoder.as_mut() else {
+                return Poll::Ready(None);
+            };
+            match ready!(Pin::new(&mut *file_stream).poll_next(cx)) {
+                Some(Ok(buf)) => match current.write(&buf) {
+                    // 压缩器可能暂时缓存了全部输入，此时继续读取
+                    Ok(out) if out.is_empty() => continue,
+                    res => return Poll::Ready(Some(res)),
+                },
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => return Poll::Ready(Some(encoder.take().unwrap().finish())),
+            }
+        }
+    }
+}
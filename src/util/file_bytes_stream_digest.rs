@@ -0,0 +1,71 @@
+/// 边读取文件边计算摘要，读取结束后以trailer的形式输出`Content-Digest`（RFC 9530）
+/// 同时输出已被取代的`Digest`（RFC 3230），兼容只认识旧头部的客户端
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_util::Stream;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use hyper::body::Bytes;
+use sha2::{Digest, Sha256};
+use std::{io::Error as IoError, mem, pin::Pin, task::Poll};
+
+use crate::vfs::{FileAccess, TokioFileAccess};
+
+use super::FileBytesStream;
+
+pub const CONTENT_DIGEST: HeaderName = HeaderName::from_static("content-digest");
+pub const DIGEST: HeaderName = HeaderName::from_static("digest");
+
+/// 包装`FileBytesStream`，对输出的每一块数据做SHA-256
+pub struct FileBytesStreamDigest<F = TokioFileAccess> {
+    file_stream: FileBytesStream<F>,
+    hasher: Sha256,
+    trailers_taken: bool,
+}
+
+impl<F> FileBytesStreamDigest<F> {
+    pub fn new(file_stream: FileBytesStream<F>) -> Self {
+        Self {
+            file_stream,
+            hasher: Sha256::new(),
+            trailers_taken: false,
+        }
+    }
+
+    /// 数据读取完毕后取出包含`Content-Digest`和`Digest`的trailer，只会返回一次
+    pub fn take_trailers(&mut self) -> Option<HeaderMap> {
+        if self.trailers_taken {
+            return None;
+        }
+        self.trailers_taken = true;
+
+        let hash = STANDARD.encode(mem::take(&mut self.hasher).finalize());
+        let mut trailers = HeaderMap::new();
+        let value = format!("sha-256=:{hash}:");
+        trailers.insert(CONTENT_DIGEST, HeaderValue::from_str(&value).unwrap());
+        let value = format!("sha-256={hash}");
+        trailers.insert(DIGEST, HeaderValue::from_str(&value).unwrap());
+        Some(trailers)
+    }
+}
+
+impl<F: FileAccess> Stream for FileBytesStreamDigest<F> {
+    type Item = Result<Bytes, IoError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let Self {
+            ref mut file_stream,
+            ref mut hasher,
+            ..
+        } = *self;
+
+        match Pin::new(file_stream).poll_next(cx) {
+            Poll::Ready(Some(Ok(buf))) => {
+                hasher.update(&buf);
+                Poll::Ready(Some(Ok(buf)))
+            }
+            other => other,
+        }
+    }
+}
@@ -0,0 +1,109 @@
+/// 在文件内容前后追加固定的字节，如在每个HTML文件中注入脚本
+use futures_util::Stream;
+use hyper::body::Bytes;
+use std::{io::Error as IoError, pin::Pin, task::Poll};
+
+use crate::vfs::{FileAccess, TokioFileAccess};
+
+use super::FileBytesStream;
+
+/// 包装响应体的配置
+#[derive(Clone, Debug, Default)]
+pub struct BodyWrapper {
+    pub prefix: Bytes,
+    pub suffix: Bytes,
+    /// 只包装该MIME类型（不含参数）的文件，为None时包装所有文件
+    pub mime_type: Option<String>,
+}
+
+impl BodyWrapper {
+    pub fn new(prefix: impl Into<Bytes>, suffix: impl Into<Bytes>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            suffix: suffix.into(),
+            mime_type: None,
+        }
+    }
+
+    pub fn mime_type(&mut self, value: Option<String>) -> &mut Self {
+        self.mime_type = value;
+        self
+    }
+
+    /// 是否包装该Content-Type的文件
+    pub fn matches(&self, content_type: Option<&str>) -> bool {
+        let Some(ref mime_type) = self.mime_type else {
+            return true;
+        };
+        content_type
+            .and_then(|v| v.split(';').next())
+            .is_some_and(|v| v.trim().eq_ignore_ascii_case(mime_type))
+    }
+
+    /// 包装后增加的字节数
+    pub fn extra_length(&self) -> u64 {
+        (self.prefix.len() + self.suffix.len()) as u64
+    }
+}
+
+/// 依次输出`prefix`、文件内容和`suffix`
+pub struct FileBytesStreamWrapped<F = TokioFileAccess> {
+    prefix: Bytes,
+    file_stream: FileBytesStream<F>,
+    file_done: bool,
+    suffix: Bytes,
+}
+
+impl<F> FileBytesStreamWrapped<F> {
+    pub fn new(file_stream: FileBytesStream<F>, wrapper: &BodyWrapper) -> Self {
+        Self {
+            prefix: wrapper.prefix.clone(),
+            file_stream,
+            file_done: false,
+            suffix: wrapper.suffix.clone(),
+        }
+    }
+
+    /// 尚未输出的字节数，包括还没输出的前后缀
+    pub fn remaining(&self) -> Option<u64> {
+        let file_remaining = if self.file_done {
+            0
+        } else {
+            self.file_stream.remaining()?
+        };
+        Some(self.prefix.len() as u64 + file_remaining + self.suffix.len() as u64)
+    }
+}
+
+impl<F: FileAccess> Stream for FileBytesStreamWrapped<F> {
+    type Item = Result<Bytes, IoError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let Self {
+            ref mut prefix,
+            ref mut file_stream,
+            ref mut file_done,
+            ref mut suffix,
+        } = *self;
+
+        if !prefix.is_empty() {
+            return Poll::Ready(Some(Ok(std::mem::take(prefix))));
+        }
+
+        if !*file_done {
+            match Pin::new(file_stream).poll_next(cx) {
+                Poll::Ready(None) => *file_done = true,
+                other => return other,
+            }
+        }
+
+        if suffix.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Ready(Some(Ok(std::mem::take(suffix))))
+        }
+    }
+}
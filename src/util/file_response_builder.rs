@@ -1,5 +1,10 @@
+use std::io::Error as IoError;
+use std::path::PathBuf;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use futures_util::Stream;
+use hyper::body::Bytes;
+
 use http::{
     header, response::Builder as HttpResponseBuilder, HeaderMap, Method, Request, Response, Result,
     StatusCode,
@@ -7,21 +12,79 @@ use http::{
 use http_range::{HttpRange, HttpRangeParseError};
 use rand::{rng, seq::IndexedRandom, thread_rng};
 
-use crate::{body::Body, resolve::ResolvedFile, vfs::IntoFileAccess};
+use crate::{
+    body::Body,
+    resolve::{Encoding, ResolvedFile, PRIORITY},
+    vfs::IntoFileAccess,
+};
 
-use super::{FileBytesStream, FileBytesStreamMultiRange, FileBytesStreamRange};
+use super::{
+    BodyWrapper, ByteBudget, CacheControl, ContentDisposition, Cors, FileBytesStream,
+    FileBytesStreamMultiRange, FileBytesStreamRange, FileBytesStreamWrapped, RangeOrder,
+};
 
 const MIN_VALID_MTIME: Duration = Duration::from_secs(2);
 const BOUNDARY_LENGTH: usize = 60;
 const BOUNDARY_CHARS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+/// 默认最多输出的范围数
+const DEFAULT_MAX_RANGES: usize = 16;
+
+/// 实际输出的文件，开启`served_file_extension`后放入响应的`Extensions`，供日志和统计使用
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServedFile {
+    /// 实际输出的文件路径，命中预压缩文件时是`app.js.br`这样的路径
+    pub path: PathBuf,
+    pub size: u64,
+    pub encoding: Option<Encoding>,
+}
 
 #[derive(Clone, Debug, Default)]
 pub struct FileResponseBuilder {
     pub cache_headers: Option<u32>,
+    /// 完整的Cache-Control配置，优先于`cache_headers`
+    pub cache_control: Option<CacheControl>,
+    /// 没有其他缓存策略时使用的Cache-Control
+    pub default_cache_control: Option<String>,
+    /// 输出`Content-Disposition`，文件名取自文件路径的最后一段
+    pub content_disposition: Option<ContentDisposition>,
+    /// 在文件内容前后追加固定内容，包装后的响应不支持范围请求
+    pub body_wrapper: Option<BodyWrapper>,
+    /// 跨域访问的配置，来源列表需要配合请求的`Origin`使用
+    pub cors: Option<Cors>,
+    pub origin: Option<String>,
     pub is_head: bool,
     pub if_modified_since: Option<SystemTime>,
+    pub if_none_match: Option<String>,
+    /// If-Match使用强比较，默认生成的弱ETag只有`*`能满足，其它值都返回412
+    /// 需要按ETag匹配时开启`Resolver::strong_etag`；没有修改时间也没有强ETag时同样只有`*`能满足
+    pub if_match: Option<String>,
     pub range: Option<String>,
     pub if_range: Option<String>,
+    /// 多段响应中各段的输出顺序
+    pub range_order: RangeOrder,
+    /// 合并重叠和相邻的范围后最多允许的范围数，超出时合并为一个覆盖全部范围的范围
+    /// 为None时使用默认值16
+    pub max_ranges: Option<usize>,
+    pub byte_budget: Option<ByteBudget>,
+    /// 文件响应体每一帧的大小，为None时每次读取直接输出
+    pub chunk_size: Option<usize>,
+    /// 在响应中回显请求的`Priority`
+    pub echo_priority: bool,
+    /// 在响应的`Extensions`中放入`ServedFile`
+    pub served_file_extension: bool,
+    /// 动态zstd压缩的级别，为0时使用zstd的默认级别
+    #[cfg(feature = "zstd")]
+    pub zstd_level: i32,
+    /// 按同时进行中的动态压缩数选择压缩级别，为None时总是使用配置的级别
+    #[cfg(feature = "compression")]
+    pub compression_load: Option<super::CompressionLoad>,
+    /// 完整响应时计算SHA-256并以trailer输出`Content-Digest`（RFC 9530）和`Digest`（RFC 3230）
+    /// 只在请求带`TE: trailers`时生效，此时不输出Content-Length，HTTP/1.1下以chunked编码发送
+    #[cfg(feature = "digest")]
+    pub digest_trailer: bool,
+    /// 请求的`TE`中包含`trailers`，客户端能够接收trailer
+    #[cfg(feature = "digest")]
+    pub accepts_trailers: bool,
 }
 
 impl FileResponseBuilder {
@@ -47,8 +110,13 @@ impl FileResponseBuilder {
 
     pub fn request_heanders(&mut self, headers: &HeaderMap) -> &mut Self {
         self.if_modified_since_header(headers.get(header::IF_MODIFIED_SINCE));
+        self.if_none_match_header(headers.get(header::IF_NONE_MATCH));
+        self.if_match_header(headers.get(header::IF_MATCH));
         self.range_header(headers.get(header::RANGE));
         self.if_range(headers.get(header::IF_RANGE));
+        self.origin_header(headers.get(header::ORIGIN));
+        #[cfg(feature = "digest")]
+        self.te_header(headers.get(header::TE));
         self
     }
 
@@ -57,6 +125,93 @@ impl FileResponseBuilder {
         self
     }
 
+    pub fn cache_control(&mut self, value: Option<CacheControl>) -> &mut Self {
+        self.cache_control = value;
+        self
+    }
+
+    pub fn default_cache_control(&mut self, value: Option<String>) -> &mut Self {
+        self.default_cache_control = value;
+        self
+    }
+
+    pub fn content_disposition(&mut self, value: Option<ContentDisposition>) -> &mut Self {
+        self.content_disposition = value;
+        self
+    }
+
+    pub fn body_wrapper(&mut self, value: Option<BodyWrapper>) -> &mut Self {
+        self.body_wrapper = value;
+        self
+    }
+
+    pub fn cors(&mut self, value: Option<Cors>) -> &mut Self {
+        self.cors = value;
+        self
+    }
+
+    pub fn origin(&mut self, value: Option<String>) -> &mut Self {
+        self.origin = value;
+        self
+    }
+
+    pub fn origin_header(&mut self, value: Option<&header::HeaderValue>) -> &mut Self {
+        self.origin = value.and_then(|v| v.to_str().ok()).map(|v| v.to_string());
+        self
+    }
+
+    #[cfg(feature = "digest")]
+    pub fn te_header(&mut self, value: Option<&header::HeaderValue>) -> &mut Self {
+        self.accepts_trailers = value.and_then(|v| v.to_str().ok()).is_some_and(|v| {
+            v.split(',').any(|t| {
+                t.split(';')
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .eq_ignore_ascii_case("trailers")
+            })
+        });
+        self
+    }
+
+    pub fn byte_budget(&mut self, value: Option<ByteBudget>) -> &mut Self {
+        self.byte_budget = value;
+        self
+    }
+
+    pub fn chunk_size(&mut self, value: Option<usize>) -> &mut Self {
+        self.chunk_size = value;
+        self
+    }
+
+    #[cfg(feature = "digest")]
+    pub fn digest_trailer(&mut self, value: bool) -> &mut Self {
+        self.digest_trailer = value;
+        self
+    }
+
+    pub fn echo_priority(&mut self, value: bool) -> &mut Self {
+        self.echo_priority = value;
+        self
+    }
+
+    pub fn served_file_extension(&mut self, value: bool) -> &mut Self {
+        self.served_file_extension = value;
+        self
+    }
+
+    #[cfg(feature = "zstd")]
+    pub fn zstd_level(&mut self, value: i32) -> &mut Self {
+        self.zstd_level = value;
+        self
+    }
+
+    #[cfg(feature = "compression")]
+    pub fn compression_load(&mut self, value: Option<super::CompressionLoad>) -> &mut Self {
+        self.compression_load = value;
+        self
+    }
+
     pub fn is_head(&mut self, value: bool) -> &mut Self {
         self.is_head = value;
         self
@@ -74,6 +229,26 @@ impl FileResponseBuilder {
         self
     }
 
+    pub fn if_none_match(&mut self, value: Option<String>) -> &mut Self {
+        self.if_none_match = value;
+        self
+    }
+
+    pub fn if_none_match_header(&mut self, value: Option<&header::HeaderValue>) -> &mut Self {
+        self.if_none_match = value.and_then(|v| v.to_str().ok()).map(|v| v.to_string());
+        self
+    }
+
+    pub fn if_match(&mut self, value: Option<String>) -> &mut Self {
+        self.if_match = value;
+        self
+    }
+
+    pub fn if_match_header(&mut self, value: Option<&header::HeaderValue>) -> &mut Self {
+        self.if_match = value.and_then(|v| v.to_str().ok()).map(|v| v.to_string());
+        self
+    }
+
     pub fn if_range(&mut self, value: Option<&header::HeaderValue>) -> &mut Self {
         if let Some(s) = value.and_then(|s| s.to_str().ok()) {
             self.if_range = Some(s.to_string());
@@ -81,6 +256,16 @@ impl FileResponseBuilder {
         self
     }
 
+    pub fn range_order(&mut self, value: RangeOrder) -> &mut Self {
+        self.range_order = value;
+        self
+    }
+
+    pub fn max_ranges(&mut self, value: Option<usize>) -> &mut Self {
+        self.max_ranges = value;
+        self
+    }
+
     pub fn range_header(&mut self, value: Option<&header::HeaderValue>) -> &mut Self {
         self.range = value.and_then(|v| v.to_str().ok()).map(|v| v.to_string());
         self
@@ -90,38 +275,110 @@ impl FileResponseBuilder {
         &self,
         file: ResolvedFile<F>,
     ) -> Result<Response<Body<F::Output>>> {
+        // 负载较高时降低动态压缩的级别，或者直接输出原文件
+        #[cfg(feature = "compression")]
+        let quality = match self.compression_load {
+            Some(ref load) if file.compress => load.quality(),
+            _ => super::CompressionQuality::Default,
+        };
+        #[cfg(feature = "compression")]
+        let mut file = file;
+        #[cfg(feature = "compression")]
+        if quality == super::CompressionQuality::Off {
+            file.compress = false;
+            file.encoding = None;
+        }
         let mut res = HttpResponseBuilder::new();
+        if self.served_file_extension {
+            res = res.extension(ServedFile {
+                path: file.path.clone(),
+                size: file.size,
+                encoding: file.encoding,
+            });
+        }
         let modified = file.modified.filter(|v| {
             v.duration_since(UNIX_EPOCH)
                 .ok()
                 .filter(|v| v >= &MIN_VALID_MTIME)
                 .is_some()
         });
+        #[cfg(feature = "compression")]
+        let compress = file.compress;
+        #[cfg(not(feature = "compression"))]
+        let compress = false;
+        // 压缩后的内容不能直接拼接
+        let wrapper = self.body_wrapper.as_ref().filter(|wrapper| {
+            file.encoding.is_none() && wrapper.matches(file.content_type.as_deref())
+        });
+        let cache_control = self.cache_control_header();
+        // no-store的资源不输出ETag和Last-Modified，同时忽略条件请求
+        let no_store = cache_control.as_deref().is_some_and(is_no_store);
         let mut range_cond_ok = self.if_range.is_none();
-        if let Some(modified) = modified {
-            if let Ok(modified_unix) = modified.duration_since(UNIX_EPOCH) {
-                if let Some(Ok(is_unix)) =
-                    self.if_modified_since.map(|v| v.duration_since(UNIX_EPOCH))
-                {
-                    return HttpResponseBuilder::new()
-                        .status(StatusCode::NOT_MODIFIED)
-                        .body(Body::Empty);
-                }
+        // 没有ETag时If-Match只有`*`能满足
+        let mut precondition_failed =
+            !no_store && self.if_match.as_deref().is_some_and(|v| v.trim() != "*");
+        // If-None-Match优先于If-Modified-Since
+        let mut not_modified = !no_store
+            && self
+                .if_none_match
+                .as_deref()
+                .is_some_and(|v| v.trim() == "*");
+        let etag = match file.etag {
+            _ if no_store => None,
+            // 动态压缩的输出不保证逐字节一致，只能作为弱ETag
+            Some(ref etag) if compress => Some(format!(
+                "W/{}{}\"",
+                etag.trim_end_matches('"'),
+                on_the_fly_suffix(file.encoding)
+            )),
+            Some(ref etag) => Some(etag.clone()),
+            None => modified
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|modified_unix| {
+                    // 动态压缩的内容与原文件不同，ETag也要区分
+                    format!(
+                        "W/\"{0}{1:x}-{2:x}.{3:x}{4}\"",
+                        file.unique_id
+                            .map(|id| format!("{id:x}-"))
+                            .unwrap_or_default(),
+                        file.size,
+                        modified_unix.as_secs(),
+                        modified_unix.subsec_nanos(),
+                        if compress {
+                            on_the_fly_suffix(file.encoding)
+                        } else {
+                            String::new()
+                        }
+                    )
+                }),
+        };
+        if let Some(etag) = etag {
+            // If-Match只能使用强比较，弱ETag只有`*`能满足
+            if let Some(ref v) = self.if_match {
+                precondition_failed = !etag_list_matches_strong(v, &etag);
+            }
 
-                let etag = format!(
-                    "w/\"{0:x}-{1:x}.{2:x}\"",
-                    file.size,
-                    modified_unix.as_secs(),
-                    modified_unix.subsec_nanos()
-                );
+            if let Some(ref v) = self.if_none_match {
+                not_modified = etag_list_matches(v, &etag);
+            }
 
-                if let Some(ref v) = self.if_range {
-                    if *v == etag {
-                        range_cond_ok = true;
-                    }
+            // If-Range只能使用强比较，弱ETag永远不满足条件
+            if let Some(ref v) = self.if_range {
+                if !is_weak_etag(v) && !is_weak_etag(&etag) && *v == etag {
+                    range_cond_ok = true;
                 }
+            }
 
-                res = res.header(header::ETAG, etag);
+            res = res.header(header::ETAG, etag);
+        }
+        if let Some(modified) = modified.filter(|_| !no_store) {
+            if let (Ok(modified_unix), Some(Ok(ims_unix)), None) = (
+                modified.duration_since(UNIX_EPOCH),
+                self.if_modified_since.map(|v| v.duration_since(UNIX_EPOCH)),
+                self.if_none_match.as_ref(),
+            ) {
+                // HTTP日期精确到秒，比较前截断亚秒部分
+                not_modified = modified_unix.as_secs() <= ims_unix.as_secs();
             }
 
             let last_modified_formatted = httpdate::fmt_http_date(modified);
@@ -131,26 +388,138 @@ impl FileResponseBuilder {
                 }
             }
 
-            res = res
-                .header(header::LAST_MODIFIED, last_modified_formatted)
-                .header(header::ACCEPT_RANGES, "bytes");
+            res = res.header(header::LAST_MODIFIED, last_modified_formatted);
         }
 
-        if let Some(seconds) = self.cache_headers {
-            res = res.header(
-                header::CACHE_CONTROL,
-                format!("public, max-age={}", seconds),
-            );
+        // 长度未知或内容与文件不一致时无法给出Content-Range，明确告知不支持范围请求
+        if compress || wrapper.is_some() {
+            res = res.header(header::ACCEPT_RANGES, "none");
+        } else if modified.is_some() {
+            res = res.header(header::ACCEPT_RANGES, "bytes");
         }
 
-        if self.is_head {
-            res = res.header(header::CONTENT_LENGTH, format!("{}", file.size));
-            return res.status(StatusCode::OK).body(Body::Empty);
+        if precondition_failed {
+            return HttpResponseBuilder::new()
+                .status(StatusCode::PRECONDITION_FAILED)
+                .body(Body::Empty);
+        }
+
+        if self.echo_priority {
+            if let Some(priority) = file.priority {
+                res = res.header(PRIORITY, priority.to_header_value());
+            }
+        }
+
+        if let Some(ref content_location) = file.content_location {
+            res = res.header(header::CONTENT_LOCATION, content_location);
+        }
+
+        let mut vary: Vec<&str> = Vec::with_capacity(file.vary.len() + 1);
+        for name in &file.vary {
+            if !vary.contains(&name.as_str()) {
+                vary.push(name.as_str());
+            }
+        }
+        if self.cors.as_ref().is_some_and(Cors::varies_by_origin) {
+            vary.push(header::ORIGIN.as_str());
+        }
+        if !vary.is_empty() {
+            res = res.header(header::VARY, vary.join(", "));
+        }
+
+        res = self.cors_headers(res);
+
+        if let Some(cache_control) = cache_control {
+            res = res.header(header::CACHE_CONTROL, cache_control);
+        }
+
+        if not_modified {
+            return res.status(StatusCode::NOT_MODIFIED).body(Body::Empty);
+        }
+
+        if let Some(disposition) = self.content_disposition {
+            if let Some(filename) = file.logical_path.file_name().and_then(|v| v.to_str()) {
+                res = res.header(
+                    header::CONTENT_DISPOSITION,
+                    disposition.to_header_value(filename),
+                );
+            }
+        }
+
+        // 预压缩文件原样输出，范围请求同样是针对压缩后的字节
+        if let Some(encoding) = file.encoding {
+            res = res.header(header::CONTENT_ENCODING, encoding.to_header_value());
+        }
+
+        // HEAD的响应头与对应的GET响应一致（包括范围请求），只是没有响应体
+        // 压缩后的长度未知，不设置Content-Length，忽略Range返回完整内容
+        #[cfg(feature = "compression")]
+        if compress {
+            if let Some(content_type) = file.content_type {
+                res = res.header(header::CONTENT_TYPE, content_type);
+            }
+            if self.is_head {
+                return res.status(StatusCode::OK).body(Body::Empty);
+            }
+            let mut body_stream =
+                FileBytesStream::new_with_limit(file.handle.into_file_access(), file.size);
+            body_stream.set_byte_budget(self.byte_budget.clone());
+            body_stream.set_chunk_size(self.chunk_size);
+            let fast = quality == super::CompressionQuality::Fast;
+            let mut body_stream = match file.encoding {
+                #[cfg(feature = "zstd")]
+                Some(Encoding::Zstd) => {
+                    let level = if fast { 1 } else { self.zstd_level };
+                    match super::FileBytesStreamCompressed::zstd(body_stream, level) {
+                        Ok(body_stream) => body_stream,
+                        Err(_) => {
+                            return HttpResponseBuilder::new()
+                                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                                .body(Body::Empty);
+                        }
+                    }
+                }
+                _ if fast => super::FileBytesStreamCompressed::gzip_with_level(body_stream, 1),
+                _ => super::FileBytesStreamCompressed::gzip(body_stream),
+            };
+            if let Some(ref load) = self.compression_load {
+                body_stream.set_in_flight(load.start());
+            }
+            return res
+                .status(StatusCode::OK)
+                .body(Body::Compressed(body_stream));
+        }
+
+        if let Some(wrapper) = wrapper {
+            res = res.header(
+                header::CONTENT_LENGTH,
+                format!("{}", file.size + wrapper.extra_length()),
+            );
+            if let Some(content_type) = file.content_type {
+                res = res.header(header::CONTENT_TYPE, content_type);
+            }
+            if self.is_head {
+                return res.status(StatusCode::OK).body(Body::Empty);
+            }
+            let mut body_stream =
+                FileBytesStream::new_with_limit(file.handle.into_file_access(), file.size);
+            body_stream.set_byte_budget(self.byte_budget.clone());
+            body_stream.set_chunk_size(self.chunk_size);
+            return res
+                .status(StatusCode::OK)
+                .body(Body::Wrapped(FileBytesStreamWrapped::new(
+                    body_stream,
+                    wrapper,
+                )));
         }
 
         let ranges = self.range.as_ref().filter(|_| range_cond_ok).and_then(|r| {
             match HttpRange::parse(r, file.size) {
-                Ok(r) => Some(Ok(r)),
+                // 空文件的后缀范围会得到长度为0的范围，无法表示为Content-Range
+                Ok(mut r) => {
+                    r.retain(|range| range.length > 0);
+                    Some(if r.is_empty() { Err(()) } else { Ok(r) })
+                }
                 Err(HttpRangeParseError::NoOverlap) => Some(Err(())),
                 Err(HttpRangeParseError::InvalidRange) => None,
             }
@@ -158,10 +527,11 @@ impl FileResponseBuilder {
 
         if let Some(ranges) = ranges {
             let ranges = match ranges {
-                Ok(r) => r,
-                Err(err) => {
+                Ok(r) => coalesce_ranges(r, self.max_ranges.unwrap_or(DEFAULT_MAX_RANGES)),
+                Err(()) => {
                     return res
                         .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                        .header(header::CONTENT_RANGE, format!("bytes */{}", file.size))
                         .body(Body::Empty);
                 }
             };
@@ -174,9 +544,14 @@ impl FileResponseBuilder {
                         content_range_header(&single_span, file.size),
                     )
                     .header(header::CONTENT_LENGTH, format!("{}", single_span.length));
+                if self.is_head {
+                    return res.status(StatusCode::PARTIAL_CONTENT).body(Body::Empty);
+                }
 
-                let body_stream =
+                let mut body_stream =
                     FileBytesStreamRange::new(file.handle.into_file_access(), single_span);
+                body_stream.set_byte_budget(self.byte_budget.clone());
+                body_stream.set_chunk_size(self.chunk_size);
                 return res
                     .status(StatusCode::PARTIAL_CONTENT)
                     .body(Body::Range(body_stream));
@@ -202,11 +577,17 @@ impl FileResponseBuilder {
                 if let Some(content_type) = file.content_type.as_ref() {
                     body_stream.set_content_type(content_type);
                 }
+                body_stream.set_byte_budget(self.byte_budget.clone());
+                body_stream.set_chunk_size(self.chunk_size);
+                body_stream.set_range_order(self.range_order);
 
                 res = res.header(
                     hyper::header::CONTENT_LENGTH,
                     format!("{}", body_stream.compute_length()),
                 );
+                if self.is_head {
+                    return res.status(StatusCode::PARTIAL_CONTENT).body(Body::Empty);
+                }
 
                 return res
                     .status(StatusCode::PARTIAL_CONTENT)
@@ -214,19 +595,176 @@ impl FileResponseBuilder {
             }
         }
 
-        res = res.header(header::CONTENT_LENGTH, format!("{}", file.size));
+        // 以trailer输出摘要时响应体长度不固定，HTTP/1.1才会使用chunked编码发送trailer
+        #[cfg(feature = "digest")]
+        let digest_trailer = self.digest_trailer && self.accepts_trailers;
+        #[cfg(not(feature = "digest"))]
+        let digest_trailer = false;
+        #[cfg(feature = "digest")]
+        if digest_trailer {
+            let trailer = format!("{}, {}", super::CONTENT_DIGEST, super::DIGEST);
+            res = res.header(header::TRAILER, trailer);
+        }
+        if !digest_trailer {
+            res = res.header(header::CONTENT_LENGTH, format!("{}", file.size));
+        }
         if let Some(content_type) = file.content_type {
             res = res.header(header::CONTENT_TYPE, content_type);
         }
-        if let Some(encoding) = file.encoding {
-            res = res.header(header::CONTENT_ENCODING, encoding.to_header_value());
+        if self.is_head {
+            return res.status(StatusCode::OK).body(Body::Empty);
+        }
+
+        let mut body_stream =
+            FileBytesStream::new_with_limit(file.handle.into_file_access(), file.size);
+        body_stream.set_byte_budget(self.byte_budget.clone());
+        body_stream.set_chunk_size(self.chunk_size);
+        #[cfg(feature = "digest")]
+        if digest_trailer {
+            return res
+                .status(StatusCode::OK)
+                .body(Body::Digest(super::FileBytesStreamDigest::new(body_stream)));
+        }
+        res.status(StatusCode::OK).body(Body::Full(body_stream))
+    }
+
+    /// 请求的来源被允许时输出CORS响应头
+    pub fn cors_headers(&self, mut res: HttpResponseBuilder) -> HttpResponseBuilder {
+        let Some(ref cors) = self.cors else {
+            return res;
+        };
+        let Some(allow_origin) = cors.allow_origin(self.origin.as_deref()) else {
+            return res;
+        };
+        res = res.header(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        if let Some(methods) = cors.allow_methods_header() {
+            res = res.header(header::ACCESS_CONTROL_ALLOW_METHODS, methods);
+        }
+        if let Some(max_age) = cors.max_age {
+            res = res.header(header::ACCESS_CONTROL_MAX_AGE, max_age.to_string());
+        }
+        res
+    }
+
+    /// 按优先级选择Cache-Control
+    fn cache_control_header(&self) -> Option<String> {
+        if let Some(ref cache_control) = self.cache_control {
+            Some(cache_control.to_string())
+        } else if let Some(seconds) = self.cache_headers {
+            Some(format!("public, max-age={}", seconds))
+        } else {
+            self.default_cache_control.clone()
+        }
+    }
+
+    /// 使用任意字节流构建响应，复用状态码、缓存和条件请求的处理
+    /// 长度未知，因此不设置Content-Length，也不支持范围请求和ETag
+    pub fn build_stream<F, S>(
+        &self,
+        stream: S,
+        modified: Option<SystemTime>,
+        content_type: Option<String>,
+    ) -> Result<Response<Body<F>>>
+    where
+        S: Stream<Item = std::result::Result<Bytes, IoError>> + Send + 'static,
+    {
+        let mut res = HttpResponseBuilder::new().header(header::ACCEPT_RANGES, "none");
+        let cache_control = self.cache_control_header();
+        let no_store = cache_control.as_deref().is_some_and(is_no_store);
+        let mut not_modified = false;
+        if let Some(modified) = modified.filter(|_| !no_store) {
+            if let (Ok(modified_unix), Some(Ok(ims_unix))) = (
+                modified.duration_since(UNIX_EPOCH),
+                self.if_modified_since.map(|v| v.duration_since(UNIX_EPOCH)),
+            ) {
+                not_modified = modified_unix.as_secs() <= ims_unix.as_secs();
+            }
+            res = res.header(header::LAST_MODIFIED, httpdate::fmt_http_date(modified));
+        }
+
+        if let Some(cache_control) = cache_control {
+            res = res.header(header::CACHE_CONTROL, cache_control);
+        }
+
+        if not_modified {
+            return res.status(StatusCode::NOT_MODIFIED).body(Body::Empty);
+        }
+
+        if let Some(content_type) = content_type {
+            res = res.header(header::CONTENT_TYPE, content_type);
         }
 
+        if self.is_head {
+            return res.status(StatusCode::OK).body(Body::Empty);
+        }
         res.status(StatusCode::OK)
-            .body(Body::Full(FileBytesStream::new_with_limit(
-                file.handle.into_file_access(),
-                file.size,
-            )))
+            .body(Body::Stream(Box::pin(stream)))
+    }
+}
+
+/// Cache-Control中是否包含`no-store`指令
+fn is_no_store(cache_control: &str) -> bool {
+    cache_control
+        .split(',')
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-store"))
+}
+
+/// 动态压缩的ETag后缀，如`-gzip`
+fn on_the_fly_suffix(encoding: Option<Encoding>) -> String {
+    encoding.map_or_else(String::new, |encoding| format!("-{}", encoding.as_str()))
+}
+
+fn is_weak_etag(etag: &str) -> bool {
+    etag.starts_with("W/")
+}
+
+/// 强比较ETag列表，弱ETag永远不匹配
+fn etag_list_matches_strong(list: &str, etag: &str) -> bool {
+    list.split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || (!is_weak_etag(tag) && tag == etag))
+}
+
+/// 弱比较`If-None-Match`或`If-Match`中逗号分隔的ETag列表，`*`匹配任意ETag
+fn etag_list_matches(list: &str, etag: &str) -> bool {
+    let opaque = etag.trim_start_matches("W/");
+    list.split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == opaque)
+}
+
+/// 合并重叠或相邻的范围，避免大量细碎的范围放大响应
+/// 没有可合并的范围时保持原有顺序，合并后的数量仍超过`max`时合并为一个范围
+fn coalesce_ranges(ranges: Vec<HttpRange>, max: usize) -> Vec<HttpRange> {
+    let mut sorted = ranges.clone();
+    sorted.sort_by_key(|range| range.start);
+    let mut merged: Vec<HttpRange> = Vec::with_capacity(sorted.len());
+    for range in sorted {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.start + last.length => {
+                let end = (last.start + last.length).max(range.start + range.length);
+                last.length = end - last.start;
+            }
+            _ => merged.push(range),
+        }
+    }
+
+    if merged.len() > max.max(1) {
+        let start = merged[0].start;
+        let end = merged
+            .iter()
+            .map(|range| range.start + range.length)
+            .max()
+            .unwrap_or(start);
+        return vec![HttpRange {
+            start,
+            length: end - start,
+        }];
+    }
+    if merged.len() == ranges.len() {
+        ranges
+    } else {
+        merged
     }
 }
 
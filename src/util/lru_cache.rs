@@ -0,0 +1,80 @@
+/// 按权重限制总量的LRU缓存，超出上限时淘汰最久没有使用的条目
+use std::collections::HashMap;
+use std::hash::Hash;
+
+struct LruEntry<V> {
+    value: V,
+    weight: u64,
+    /// 最近一次使用的序号，越小越久没有使用
+    last_used: u64,
+}
+
+pub(crate) struct LruCache<K, V> {
+    entries: HashMap<K, LruEntry<V>>,
+    total_weight: u64,
+    tick: u64,
+}
+
+impl<K, V> Default for LruCache<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            total_weight: 0,
+            tick: 0,
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> LruCache<K, V> {
+    /// 当前所有条目的权重之和
+    pub(crate) fn total_weight(&self) -> u64 {
+        self.total_weight
+    }
+
+    /// 查找缓存，`check`返回false的条目视为失效并移除
+    pub(crate) fn get(&mut self, key: &K, check: impl FnOnce(&V) -> bool) -> Option<&mut V> {
+        if !check(&self.entries.get(key)?.value) {
+            self.remove(key);
+            return None;
+        }
+        self.tick += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.tick;
+        Some(&mut entry.value)
+    }
+
+    pub(crate) fn remove(&mut self, key: &K) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.total_weight -= entry.weight;
+        }
+    }
+
+    /// 加入缓存，超出`max_weight`时先淘汰最久没有使用的条目，单个条目超出上限时不缓存
+    pub(crate) fn insert(&mut self, key: K, value: V, weight: u64, max_weight: u64) {
+        self.remove(&key);
+        if weight > max_weight {
+            return;
+        }
+        while self.total_weight + weight > max_weight {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+        self.tick += 1;
+        self.total_weight += weight;
+        self.entries.insert(
+            key,
+            LruEntry {
+                value,
+                weight,
+                last_used: self.tick,
+            },
+        );
+    }
+}
@@ -1,8 +1,37 @@
+mod autoindex;
+mod byte_budget;
+mod cache_control;
+#[cfg(feature = "compression")]
+mod compression_load;
+mod content_disposition;
+mod cors;
 mod file_bytes_stream;
+#[cfg(feature = "compression")]
+mod file_bytes_stream_compressed;
+#[cfg(feature = "digest")]
+mod file_bytes_stream_digest;
+mod file_bytes_stream_wrapped;
 mod file_response_builder;
+mod lru_cache;
 mod requested_path;
 
+pub use self::byte_budget::ByteBudget;
+pub use self::cache_control::*;
+#[cfg(feature = "compression")]
+pub use self::compression_load::{CompressionLoad, CompressionQuality};
+pub use self::content_disposition::*;
+pub use self::cors::*;
 pub use self::file_bytes_stream::*;
+#[cfg(feature = "compression")]
+pub use self::file_bytes_stream_compressed::*;
+#[cfg(feature = "digest")]
+pub use self::file_bytes_stream_digest::*;
+pub use self::file_bytes_stream_wrapped::*;
 pub use self::file_response_builder::*;
 
+pub(crate) use self::autoindex::*;
+pub(crate) use self::byte_budget::BudgetState;
+#[cfg(feature = "compression")]
+pub(crate) use self::compression_load::InFlightGuard;
+pub(crate) use self::lru_cache::LruCache;
 pub(crate) use self::requested_path::*;
\ No newline at end of file
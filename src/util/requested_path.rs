@@ -1,25 +1,48 @@
+use std::ffi::OsStr;
 use std::path::{Component, Path, PathBuf};
 
+use crate::resolve::TrailingDots;
+
 fn decode_percents(string: &str) -> String {
     percent_encoding::percent_decode_str(string)
         .decode_utf8_lossy()
         .into_owned()
 }
 
+/// 按策略处理路径片段末尾的`.`和空格，Windows打开文件时会忽略它们，`foo.txt.`实际打开的是`foo.txt`
+/// 返回None表示拒绝该请求
+fn normalize_component(component: &OsStr, trailing_dots: TrailingDots) -> Option<&OsStr> {
+    let Some(s) = component.to_str() else {
+        return Some(component);
+    };
+    let trimmed = s.trim_end_matches(['.', ' ']);
+    match trailing_dots {
+        TrailingDots::Keep => Some(component),
+        TrailingDots::Normalize => Some(OsStr::new(trimmed)),
+        TrailingDots::Reject if trimmed.len() != s.len() => None,
+        TrailingDots::Reject => Some(component),
+    }
+}
+
 /// 标准化路径，防止攻击
-fn sanitize_path(path: &Path) -> PathBuf {
-    path.components().fold(PathBuf::new(), |mut result, p| match p {
+fn sanitize_path(path: &Path, trailing_dots: TrailingDots) -> Option<PathBuf> {
+    path.components().try_fold(PathBuf::new(), |mut result, p| match p {
         Component::Normal(x) => {
-            if Path::new(&x).components().all(|c| matches!(c, Component::Normal(_))) {
+            let x = normalize_component(x, trailing_dots)?;
+            if !x.is_empty()
+                && Path::new(&x)
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)))
+            {
                 result.push(x);
             }
-            result
+            Some(result)
         }
         Component::ParentDir => {
             result.pop();
-            result
+            Some(result)
         }
-        _ => result,
+        _ => Some(result),
     })
 }
 
@@ -30,12 +53,16 @@ pub struct RequestedPath{
 
 impl RequestedPath{
     pub fn resolve(request_path: &str) -> Self {
+        Self::resolve_with(request_path, TrailingDots::Keep).expect("trailing dots are kept")
+    }
+
+    /// 按`trailing_dots`策略处理末尾的`.`和空格，拒绝时返回None
+    pub fn resolve_with(request_path: &str, trailing_dots: TrailingDots) -> Option<Self> {
         let is_dir_request = request_path.as_bytes().last() == Some(&b'/');
         let request_path = PathBuf::from(decode_percents(request_path));
-        RequestedPath {
-            sanitized: sanitize_path(&request_path),
+        Some(RequestedPath {
+            sanitized: sanitize_path(&request_path, trailing_dots)?,
             is_dir_request
-        }
+        })
     }
 }
-
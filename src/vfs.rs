@@ -3,7 +3,7 @@
 /// FileWithMetadata，主要是文件的元信息和具体的文件（文件句柄或者文件字节码），可能会是文件或者文件夹
 /// 主要接口：
 /// FileAccess : 提供读取的future接口，返回字节码（主要针对文件）
-/// FileOpener ： 提供打开`path`文件的future接口，返回文件元信息（针对文件和文件夹）
+/// FileOpener ： 提供打开`path`文件的future接口，返回文件元信息（针对文件和文件夹），也可以列出目录
 /// 实现类：
 /// TokioFileFuture：包装一个Future，返回`FileWithMetadata`
 ///
@@ -11,23 +11,35 @@
 ///     它主要支持打开一个`root`目录，使用根目录`root`和`path`相结合
 /// TokioFileAccess：实现FileAccess.包装tokio::fs，返回文件元信息（TokioFuture）
 ///     主要针对的是单个文件
+///
+/// IncludeDirOpener: 实现FileOpener，直接读取`include_dir`嵌入的文件
+///
+/// CachingOpener: 包装任意FileOpener，按LRU在内存中缓存小文件的内容
 use std::cmp::min;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::OpenOptions;
-use std::io::{Cursor, Error, ErrorKind};
+use std::io::{Cursor, Error, ErrorKind, SeekFrom};
 use std::mem::MaybeUninit;
 use std::path::{Component, Path, PathBuf};
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use std::{future::Future, time::SystemTime};
 
-use futures_util::future::{ready, Ready};
+use futures_util::future::{ready, BoxFuture, Ready};
+use futures_util::StreamExt;
 use hyper::body::Bytes;
+#[cfg(feature = "include_dir")]
 use include_dir::DirEntry;
+use mime_guess::{Mime, MimeGuess};
 use tokio::fs::{self, File};
 use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
 use tokio::task::{spawn_blocking, JoinHandle};
 
+use crate::resolve::{default_compressible_types, Encoding};
+use crate::util::{FileBytesStream, LruCache};
+
 const TOKIO_READ_BUF_SIZE: usize = 8 * 1024;
 
 /// 文件元信息
@@ -39,6 +51,18 @@ pub struct FileWithMetadata<F = File> {
     pub size: u64,
     pub modified: Option<SystemTime>,
     pub is_dir: bool,
+    /// 文件在文件系统中的唯一标识，如Unix上的设备号和inode，不支持时为None
+    /// 设置后参与生成ETag，同一文件的不同路径（如硬链接）得到相同的标识
+    pub unique_id: Option<u64>,
+}
+
+/// 目录中的一个条目
+#[derive(Debug, Clone)]
+pub struct DirectoryEntry {
+    pub name: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub is_dir: bool,
 }
 
 /// 打开文件
@@ -46,6 +70,12 @@ pub trait FileOpener: Send + Sync + 'static {
     type File: IntoFileAccess;
     type Future: Future<Output = Result<FileWithMetadata<Self::File>, Error>> + Send;
     fn open(&self, path: &Path) -> Self::Future;
+
+    /// 列出目录下的条目，用于生成目录索引，默认不支持
+    fn read_dir(&self, path: &Path) -> BoxFuture<'static, Result<Vec<DirectoryEntry>, Error>> {
+        let _ = path;
+        Box::pin(ready(Err(Error::from(ErrorKind::Unsupported))))
+    }
 }
 
 /// 转为读取文件
@@ -128,11 +158,49 @@ impl FileAccess for TokioFileAccess {
 
 pub struct TokioFileOpener {
     pub root: PathBuf,
+    /// 是否跟随指向`root`之外的符号链接，为false时这样的路径返回PermissionDenied
+    pub follow_symlinks: bool,
 }
 
 impl TokioFileOpener {
     pub fn new(root: impl Into<PathBuf>) -> Self {
-        Self { root: root.into() }
+        Self {
+            root: root.into(),
+            follow_symlinks: true,
+        }
+    }
+
+    pub fn follow_symlinks(&mut self, value: bool) -> &mut Self {
+        self.follow_symlinks = value;
+        self
+    }
+}
+
+/// 由设备号和inode组成的文件标识
+#[cfg(unix)]
+fn unique_id(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev().rotate_left(32) ^ metadata.ino())
+}
+
+/// Windows上的文件索引还需要不稳定的API，暂不提供
+#[cfg(not(unix))]
+fn unique_id(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// 解析路径中所有的符号链接，结果不在`root`之内时返回PermissionDenied
+/// `canonicalize`在Windows上同样会解析符号链接和目录联接
+fn canonicalize_within(root: &Path, path: &Path) -> Result<PathBuf, Error> {
+    let root = std::fs::canonicalize(root)?;
+    let path = std::fs::canonicalize(path)?;
+    if path.starts_with(&root) {
+        Ok(path)
+    } else {
+        Err(Error::new(
+            ErrorKind::PermissionDenied,
+            "path escapes the root through a symlink",
+        ))
     }
 }
 
@@ -142,8 +210,15 @@ impl FileOpener for TokioFileOpener {
     fn open(&self, path: &Path) -> Self::Future {
         let mut full_path = self.root.clone();
         full_path.extend(path);
+        let root = (!self.follow_symlinks).then(|| self.root.clone());
 
         let inner = spawn_blocking(move || {
+            // 打开检查过的路径，而不是再解析一次原路径
+            let full_path = match root {
+                Some(root) => canonicalize_within(&root, &full_path)?,
+                None => full_path,
+            };
+
             let mut opts = OpenOptions::new();
             opts.read(true);
 
@@ -158,11 +233,55 @@ impl FileOpener for TokioFileOpener {
                 size: metadata.len(),
                 modified: metadata.modified().ok(),
                 is_dir: metadata.is_dir(),
+                unique_id: unique_id(&metadata),
             })
         });
 
         TokioFileFuture { inner }
     }
+
+    fn read_dir(&self, path: &Path) -> BoxFuture<'static, Result<Vec<DirectoryEntry>, Error>> {
+        let mut full_path = self.root.clone();
+        full_path.extend(path);
+        let root = (!self.follow_symlinks).then(|| self.root.clone());
+
+        Box::pin(async move {
+            let full_path = match root {
+                Some(ref root) => {
+                    let root = root.clone();
+                    spawn_blocking(move || canonicalize_within(&root, &full_path)).await??
+                }
+                None => full_path,
+            };
+
+            let mut entries = Vec::new();
+            let mut iter = fs::read_dir(full_path).await?;
+            while let Some(entry) = iter.next_entry().await? {
+                // 指向`root`之外的链接不列出
+                if let Some(ref root) = root {
+                    if entry.file_type().await?.is_symlink() {
+                        let root = root.clone();
+                        let path = entry.path();
+                        let within = spawn_blocking(move || canonicalize_within(&root, &path));
+                        if within.await?.is_err() {
+                            continue;
+                        }
+                    }
+                }
+                // 跟随符号链接，失效的链接直接跳过
+                let Ok(metadata) = fs::metadata(entry.path()).await else {
+                    continue;
+                };
+                entries.push(DirectoryEntry {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    size: metadata.len(),
+                    modified: metadata.modified().ok(),
+                    is_dir: metadata.is_dir(),
+                });
+            }
+            Ok(entries)
+        })
+    }
 }
 
 /// 包装文件的Future，返回文件的元信息
@@ -212,7 +331,11 @@ impl FileAccess for Cursor<Bytes> {
         // 这里计算需要读取的字节数，防止数组越界
         let amt = min(slice.len() - start, len);
         let end = start + amt;
-        Poll::Ready(Ok(slice.slice(start..end)))
+        // slice只增加引用计数，不复制数据
+        let buf = slice.slice(start..end);
+        // 分块读取时下一次要从这次结束的位置继续
+        self.set_position(end as u64);
+        Poll::Ready(Ok(buf))
     }
 }
 
@@ -220,6 +343,7 @@ pub struct MemoryFs {
     files: MemoryFileMap,
 }
 
+#[cfg(feature = "include_dir")]
 impl Into<MemoryFs> for &'static include_dir::Dir<'static> {
     fn into(self) -> MemoryFs {
         MemoryFs::from_include_dir(self).unwrap()
@@ -236,6 +360,7 @@ impl Default for MemoryFs {
                 size: 0,
                 modified: None,
                 is_dir: true,
+                unique_id: None,
             },
         );
 
@@ -243,9 +368,115 @@ impl Default for MemoryFs {
     }
 }
 
+/// 按相对路径筛选文件的函数
+pub type PathFilter = Arc<dyn Fn(&Path) -> bool + Send + Sync>;
+
+/// `MemoryFs::from_dir_with`的选项
+#[derive(Clone, Default)]
+pub struct FromDirOptions {
+    /// 按相对路径筛选文件和文件夹，返回false时跳过，文件夹被跳过时其中的内容都不会读取
+    pub filter: Option<PathFilter>,
+    /// 读取的文件总大小上限，超出时返回错误
+    pub max_total_bytes: Option<u64>,
+    /// 读取完成后生成预压缩文件
+    pub precompress: Option<Precompress>,
+}
+
+impl FromDirOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn filter(&mut self, value: impl Fn(&Path) -> bool + Send + Sync + 'static) -> &mut Self {
+        self.filter = Some(Arc::new(value));
+        self
+    }
+
+    pub fn max_total_bytes(&mut self, value: Option<u64>) -> &mut Self {
+        self.max_total_bytes = value;
+        self
+    }
+
+    pub fn precompress(&mut self, value: Option<Precompress>) -> &mut Self {
+        self.precompress = value;
+        self
+    }
+}
+
+/// 在内存中生成预压缩文件的配置，生成的文件与磁盘上的预压缩文件一样由`Resolver`查找
+#[derive(Clone, Debug)]
+pub struct Precompress {
+    /// gzip需要`compression` feature，br需要`brotli` feature
+    pub encodings: Vec<Encoding>,
+    /// 压缩级别，gzip为0-9，br为0-11，超出时取最大值
+    pub level: u32,
+    /// 只压缩这些类型的文件，图片、字体等已经压缩过的文件不在其中
+    pub compressible_types: HashSet<Mime>,
+}
+
+impl Precompress {
+    pub fn new(encodings: Vec<Encoding>) -> Self {
+        Self {
+            encodings,
+            level: 6,
+            compressible_types: default_compressible_types(),
+        }
+    }
+
+    pub fn level(&mut self, value: u32) -> &mut Self {
+        self.level = value;
+        self
+    }
+
+    pub fn compressible_types(&mut self, value: HashSet<Mime>) -> &mut Self {
+        self.compressible_types = value;
+        self
+    }
+
+    #[cfg_attr(
+        not(any(feature = "compression", feature = "brotli")),
+        allow(unused_variables)
+    )]
+    fn compress(&self, encoding: Encoding, data: &[u8]) -> Result<Vec<u8>, Error> {
+        #[cfg(any(feature = "compression", feature = "brotli"))]
+        use std::io::Write;
+
+        match encoding {
+            #[cfg(feature = "compression")]
+            Encoding::Gzip => {
+                let level = flate2::Compression::new(self.level.min(9));
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), level);
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "brotli")]
+            Encoding::Br => {
+                let mut encoder =
+                    brotli::CompressorWriter::new(Vec::new(), 4096, self.level.min(11), 22);
+                encoder.write_all(data)?;
+                encoder.flush()?;
+                Ok(encoder.into_inner())
+            }
+            _ => Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("precompressing with {encoding:?} is not enabled"),
+            )),
+        }
+    }
+}
+
 impl MemoryFs {
     pub async fn from_dir(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_dir_with(path, &FromDirOptions::default()).await
+    }
+
+    /// 读取文件夹，可以筛选文件并限制读入内存的总大小
+    pub async fn from_dir_with(
+        path: impl AsRef<Path>,
+        options: &FromDirOptions,
+    ) -> Result<Self, Error> {
         let mut fs = Self::default();
+        let mut total_bytes = 0u64;
         let mut dirs = vec![(path.as_ref().to_path_buf(), PathBuf::new())];
         while let Some((dir, base)) = dirs.pop() {
             let mut iter = fs::read_dir(dir).await?;
@@ -253,18 +484,63 @@ impl MemoryFs {
                 let metadata = entry.metadata().await?;
                 let mut out_path = base.to_path_buf();
                 out_path.push(entry.file_name());
+                if let Some(ref filter) = options.filter {
+                    if !filter(&out_path) {
+                        continue;
+                    }
+                }
 
                 if metadata.is_dir() {
                     dirs.push((entry.path(), out_path));
                 } else if metadata.is_file() {
+                    // 读取前先按元数据中的大小检查，避免读入超大文件
+                    total_bytes += metadata.len();
+                    if options.max_total_bytes.is_some_and(|max| total_bytes > max) {
+                        return Err(Error::other(format!(
+                            "directory exceeds the limit of {} bytes",
+                            options.max_total_bytes.unwrap_or_default()
+                        )));
+                    }
                     let data = fs::read(entry.path()).await?;
                     fs.add(out_path, data.into(), metadata.modified().ok());
                 }
             }
         }
+        if let Some(ref precompress) = options.precompress {
+            fs.precompress(precompress)?;
+        }
         Ok(fs)
     }
 
+    /// 为可压缩的文件生成`.gz`、`.br`等预压缩文件，已存在或压缩后没有变小时跳过
+    pub fn precompress(&mut self, options: &Precompress) -> Result<&mut Self, Error> {
+        let mut compressed = Vec::new();
+        for (path, file) in &self.files {
+            let compressible = !file.is_dir
+                && MimeGuess::from_path(path)
+                    .first()
+                    .is_some_and(|mimetype| options.compressible_types.contains(&mimetype));
+            if !compressible {
+                continue;
+            }
+            for &encoding in &options.encodings {
+                let sidecar_path = encoding.sidecar_path(path);
+                if self.files.contains_key(&sidecar_path) {
+                    continue;
+                }
+                let data = options.compress(encoding, &file.handle)?;
+                if (data.len() as u64) < file.size {
+                    compressed.push((sidecar_path, Bytes::from(data), file.modified));
+                }
+            }
+        }
+        for (path, data, modified) in compressed {
+            self.add(path, data, modified);
+        }
+        Ok(self)
+    }
+
+    #[cfg(feature = "include_dir")]
     pub fn from_include_dir(dir: &'static include_dir::Dir) -> Result<Self, Error> {
         let mut fs = Self::default();
         let mut dirs = vec![dir];
@@ -274,10 +550,15 @@ impl MemoryFs {
                     DirEntry::Dir(d) => dirs.push(d),
                     DirEntry::File(file) => {
                         let data = file.contents();
+                        // 嵌入时记录的修改时间不随进程重启变化，ETag因此保持稳定
+                        #[cfg(feature = "include-dir-metadata")]
+                        let modified = file.metadata().map(|m| m.modified());
+                        #[cfg(not(feature = "include-dir-metadata"))]
+                        let modified = None;
                         fs.add(
                             file.path(),
                             Bytes::from_static(data),
-                            Some(SystemTime::now()),
+                            modified.or_else(|| Some(SystemTime::now())),
                         );
                     }
                 }
@@ -286,6 +567,37 @@ impl MemoryFs {
         Ok(fs)
     }
 
+    /// 从`.tar.zst`归档构建，归档只在此处解压一次，之后全部从内存读取
+    #[cfg(feature = "archive")]
+    pub fn from_tar_zst(reader: impl std::io::Read) -> Result<Self, Error> {
+        use std::io::Read;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let mut fs = Self::default();
+        let mut archive = tar::Archive::new(zstd::stream::read::Decoder::new(reader)?);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            // 只保留普通路径部分，去掉`./`并忽略`..`
+            let path: PathBuf = entry
+                .path()?
+                .components()
+                .filter(|c| matches!(c, Component::Normal(_)))
+                .collect();
+            let modified = entry
+                .header()
+                .mtime()
+                .ok()
+                .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+            let mut data = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut data)?;
+            fs.add(path, data.into(), modified);
+        }
+        Ok(fs)
+    }
+
     pub fn add(
         &mut self,
         path: impl Into<PathBuf>,
@@ -300,18 +612,19 @@ impl MemoryFs {
         components.pop();
         let mut dir_path = PathBuf::new();
         // 遍历文件的全部文件夹
+        // 同名的普通文件优先，不会被文件夹占位覆盖
         for component in components {
             if let Component::Normal(x) = component {
                 dir_path.push(x);
-                self.files.insert(
-                    dir_path.clone(),
-                    FileWithMetadata {
+                self.files
+                    .entry(dir_path.clone())
+                    .or_insert_with(|| FileWithMetadata {
                         handle: Bytes::new(),
                         size: 0,
                         modified: None,
                         is_dir: true,
-                    },
-                );
+                        unique_id: None,
+                    });
             }
         }
 
@@ -324,11 +637,38 @@ impl MemoryFs {
                 size,
                 modified,
                 is_dir: false,
+                unique_id: None,
             },
         );
 
         self
     }
+
+    /// 删除文件并返回其内容，文件夹不会被删除，即使已经为空
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> Option<FileWithMetadata<Bytes>> {
+        let path = path.as_ref();
+        if self.files.get(path)?.is_dir {
+            return None;
+        }
+        self.files.remove(path)
+    }
+
+    /// 是否存在该文件，文件夹不算在内
+    pub fn contains(&self, path: impl AsRef<Path>) -> bool {
+        self.get(path).is_some()
+    }
+
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&FileWithMetadata<Bytes>> {
+        self.files.get(path.as_ref()).filter(|file| !file.is_dir)
+    }
+
+    /// 遍历所有文件的路径，不包含文件夹，顺序不固定
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .filter(|(_, file)| !file.is_dir)
+            .map(|(path, _)| path.as_path())
+    }
 }
 
 /// 实现内存文件的打开文件
@@ -344,8 +684,328 @@ impl FileOpener for MemoryFs {
                     size: file.size,
                     modified: file.modified,
                     is_dir: file.is_dir,
+                    unique_id: file.unique_id,
                 })
-                .ok_or_else(|| Error::new(ErrorKind::NotFound, "Not Found")),
+                // 预压缩文件的探测大多会落空，使用不分配内存的简单错误
+                .ok_or_else(|| Error::from(ErrorKind::NotFound)),
         )
     }
+
+    fn read_dir(&self, path: &Path) -> BoxFuture<'static, Result<Vec<DirectoryEntry>, Error>> {
+        let entries = match self.files.get(path) {
+            Some(dir) if dir.is_dir => Ok(self
+                .files
+                .iter()
+                .filter(|(child, _)| child.parent() == Some(path))
+                .map(|(child, file)| DirectoryEntry {
+                    name: child
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned(),
+                    size: file.size,
+                    modified: file.modified,
+                    is_dir: file.is_dir,
+                })
+                .collect()),
+            Some(_) => Err(Error::from(ErrorKind::NotADirectory)),
+            None => Err(Error::from(ErrorKind::NotFound)),
+        };
+        Box::pin(ready(entries))
+    }
+}
+
+// include_dir嵌入的文件
+
+/// 直接从`include_dir::Dir`中按路径查找文件，不需要像`MemoryFs`那样预先建立索引
+/// 文件内容直接引用嵌入的数据，不会复制
+#[cfg(feature = "include_dir")]
+pub struct IncludeDirOpener {
+    pub dir: &'static include_dir::Dir<'static>,
+    /// 没有嵌入修改时间时使用的修改时间，默认为创建时的时间
+    pub modified: Option<SystemTime>,
+}
+
+#[cfg(feature = "include_dir")]
+impl IncludeDirOpener {
+    pub fn new(dir: &'static include_dir::Dir<'static>) -> Self {
+        Self {
+            dir,
+            modified: Some(SystemTime::now()),
+        }
+    }
+
+    /// 逐级查找，`path`相对于`dir`，空路径表示`dir`本身
+    fn lookup(&self, path: &Path) -> Option<IncludeDirEntry> {
+        let mut current = IncludeDirEntry::Dir(self.dir);
+        let mut full_path = self.dir.path().to_path_buf();
+        for component in path.components() {
+            full_path.push(component);
+            let entries = match current {
+                IncludeDirEntry::Dir(dir) => dir.entries(),
+                IncludeDirEntry::File(_) => return None,
+            };
+            current = match entries.iter().find(|entry| entry.path() == full_path)? {
+                DirEntry::Dir(dir) => IncludeDirEntry::Dir(dir),
+                DirEntry::File(file) => IncludeDirEntry::File(file),
+            };
+        }
+        Some(current)
+    }
+
+    fn file_modified(&self, file: &include_dir::File<'static>) -> Option<SystemTime> {
+        #[cfg(feature = "include-dir-metadata")]
+        if let Some(metadata) = file.metadata() {
+            return Some(metadata.modified());
+        }
+        let _ = file;
+        self.modified
+    }
+}
+
+#[cfg(feature = "include_dir")]
+enum IncludeDirEntry {
+    Dir(&'static include_dir::Dir<'static>),
+    File(&'static include_dir::File<'static>),
+}
+
+#[cfg(feature = "include_dir")]
+impl FileOpener for IncludeDirOpener {
+    type File = Cursor<Bytes>;
+    type Future = Ready<Result<FileWithMetadata<Self::File>, Error>>;
+
+    fn open(&self, path: &Path) -> Self::Future {
+        let file = match self.lookup(path) {
+            Some(IncludeDirEntry::Dir(_)) => Ok(FileWithMetadata {
+                handle: Cursor::new(Bytes::new()),
+                size: 0,
+                modified: None,
+                is_dir: true,
+                unique_id: None,
+            }),
+            Some(IncludeDirEntry::File(file)) => Ok(FileWithMetadata {
+                handle: Cursor::new(Bytes::from_static(file.contents())),
+                size: file.contents().len() as u64,
+                modified: self.file_modified(file),
+                is_dir: false,
+                unique_id: None,
+            }),
+            None => Err(Error::from(ErrorKind::NotFound)),
+        };
+        ready(file)
+    }
+
+    fn read_dir(&self, path: &Path) -> BoxFuture<'static, Result<Vec<DirectoryEntry>, Error>> {
+        let entries = match self.lookup(path) {
+            Some(IncludeDirEntry::Dir(dir)) => Ok(dir
+                .entries()
+                .iter()
+                .map(|entry| {
+                    let name = entry
+                        .path()
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned();
+                    match entry {
+                        DirEntry::Dir(_) => DirectoryEntry {
+                            name,
+                            size: 0,
+                            modified: None,
+                            is_dir: true,
+                        },
+                        DirEntry::File(file) => DirectoryEntry {
+                            name,
+                            size: file.contents().len() as u64,
+                            modified: self.file_modified(file),
+                            is_dir: false,
+                        },
+                    }
+                })
+                .collect()),
+            Some(IncludeDirEntry::File(_)) => Err(Error::from(ErrorKind::NotADirectory)),
+            None => Err(Error::from(ErrorKind::NotFound)),
+        };
+        Box::pin(ready(entries))
+    }
+}
+
+// 带缓存的文件
+
+/// `CachingOpener`打开的文件，命中缓存时是内存中的内容，否则是原始文件
+pub enum CachedFile<F> {
+    Memory(Cursor<Bytes>),
+    Inner(F),
+}
+
+impl<F: IntoFileAccess> IntoFileAccess for CachedFile<F> {
+    type Output = CachedFile<F::Output>;
+    fn into_file_access(self) -> Self::Output {
+        match self {
+            CachedFile::Memory(cursor) => CachedFile::Memory(cursor),
+            CachedFile::Inner(file) => CachedFile::Inner(file.into_file_access()),
+        }
+    }
+}
+
+impl<F: FileAccess> AsyncSeek for CachedFile<F> {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> Result<(), Error> {
+        match self.get_mut() {
+            CachedFile::Memory(cursor) => Pin::new(cursor).start_seek(position),
+            CachedFile::Inner(file) => Pin::new(file).start_seek(position),
+        }
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<u64, Error>> {
+        match self.get_mut() {
+            CachedFile::Memory(cursor) => Pin::new(cursor).poll_complete(cx),
+            CachedFile::Inner(file) => Pin::new(file).poll_complete(cx),
+        }
+    }
+}
+
+impl<F: FileAccess> FileAccess for CachedFile<F> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        len: usize,
+    ) -> Poll<Result<Bytes, Error>> {
+        match self.get_mut() {
+            CachedFile::Memory(cursor) => FileAccess::poll_read(Pin::new(cursor), cx, len),
+            CachedFile::Inner(file) => Pin::new(file).poll_read(cx, len),
+        }
+    }
+}
+
+struct CacheEntry {
+    data: Bytes,
+    modified: Option<SystemTime>,
+    unique_id: Option<u64>,
+    /// 上一次确认文件没有变化的时间
+    checked_at: Instant,
+}
+
+/// 按路径缓存的文件内容，权重为文件的字节数
+type CacheState = LruCache<PathBuf, CacheEntry>;
+
+fn memory_file<F>(entry: &CacheEntry) -> FileWithMetadata<CachedFile<F>> {
+    FileWithMetadata {
+        handle: CachedFile::Memory(Cursor::new(entry.data.clone())),
+        size: entry.data.len() as u64,
+        modified: entry.modified,
+        is_dir: false,
+        unique_id: entry.unique_id,
+    }
+}
+
+/// 包装任意`FileOpener`，把小文件的内容缓存在内存中
+/// 缓存的总字节数不超过`max_bytes`，超出时淘汰最久没有使用的文件
+/// 距上一次检查超过`revalidate_after`的条目会重新打开文件，修改时间或大小变化时重新读取
+pub struct CachingOpener<O> {
+    pub inner: Arc<O>,
+    pub max_bytes: u64,
+    pub revalidate_after: Duration,
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl<O: FileOpener> CachingOpener<O> {
+    pub fn new(inner: O, max_bytes: u64) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            max_bytes,
+            revalidate_after: Duration::from_secs(1),
+            state: Arc::default(),
+        }
+    }
+
+    pub fn revalidate_after(&mut self, value: Duration) -> &mut Self {
+        self.revalidate_after = value;
+        self
+    }
+
+    /// 当前缓存的总字节数
+    pub fn cached_bytes(&self) -> u64 {
+        self.state.lock().unwrap().total_weight()
+    }
+}
+
+impl<O: FileOpener> FileOpener for CachingOpener<O> {
+    type File = CachedFile<O::File>;
+    type Future = BoxFuture<'static, Result<FileWithMetadata<Self::File>, Error>>;
+
+    fn open(&self, path: &Path) -> Self::Future {
+        let path = path.to_path_buf();
+        let inner = self.inner.clone();
+        let state = self.state.clone();
+        let max_bytes = self.max_bytes;
+        let revalidate_after = self.revalidate_after;
+
+        Box::pin(async move {
+            // 最近检查过的条目直接使用，不访问内部的opener
+            if let Some(entry) = state
+                .lock()
+                .unwrap()
+                .get(&path, |entry| entry.checked_at.elapsed() < revalidate_after)
+            {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(path = %path.display(), "file cache hit");
+                return Ok(memory_file(entry));
+            }
+
+            let file = inner.open(&path).await?;
+            if file.is_dir || file.size > max_bytes {
+                state.lock().unwrap().remove(&path);
+                return Ok(FileWithMetadata {
+                    handle: CachedFile::Inner(file.handle),
+                    size: file.size,
+                    modified: file.modified,
+                    is_dir: file.is_dir,
+                    unique_id: file.unique_id,
+                });
+            }
+
+            // 文件没有变化时复用缓存的内容
+            if let Some(entry) = state.lock().unwrap().get(&path, |entry| {
+                file.modified.is_some()
+                    && entry.modified == file.modified
+                    && entry.unique_id == file.unique_id
+                    && entry.data.len() as u64 == file.size
+            }) {
+                entry.checked_at = Instant::now();
+                #[cfg(feature = "tracing")]
+                tracing::debug!(path = %path.display(), "file cache revalidated");
+                return Ok(memory_file(entry));
+            }
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(path = %path.display(), size = file.size, "file cache miss");
+            let mut data = Vec::with_capacity(file.size as usize);
+            let mut stream =
+                FileBytesStream::new_with_limit(file.handle.into_file_access(), file.size);
+            while let Some(chunk) = stream.next().await {
+                data.extend_from_slice(&chunk?);
+            }
+            let data = Bytes::from(data);
+            let cached = FileWithMetadata {
+                handle: CachedFile::Memory(Cursor::new(data.clone())),
+                size: file.size,
+                modified: file.modified,
+                is_dir: false,
+                unique_id: file.unique_id,
+            };
+            let weight = data.len() as u64;
+            let entry = CacheEntry {
+                data,
+                modified: file.modified,
+                unique_id: file.unique_id,
+                checked_at: Instant::now(),
+            };
+            state.lock().unwrap().insert(path, entry, weight, max_bytes);
+            Ok(cached)
+        })
+    }
+
+    fn read_dir(&self, path: &Path) -> BoxFuture<'static, Result<Vec<DirectoryEntry>, Error>> {
+        self.inner.read_dir(path)
+    }
 }
@@ -0,0 +1,49 @@
+#![cfg(feature = "archive")]
+
+mod common;
+
+use common::{get, serve, APP_JS, INDEX_HTML, MTIME_SECS};
+use http::{header, StatusCode};
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+/// 在内存中生成`.tar.zst`归档
+fn tar_zst(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (path, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(MTIME_SECS);
+        header.set_cksum();
+        builder.append_data(&mut header, path, *data).unwrap();
+    }
+    let tar = builder.into_inner().unwrap();
+    zstd::stream::encode_all(&tar[..], 3).unwrap()
+}
+
+#[tokio::test]
+async fn serves_nested_file_from_tar_zst() {
+    let archive = tar_zst(&[("./index.html", INDEX_HTML), ("./assets/js/app.js", APP_JS)]);
+    let fs = MemoryFs::from_tar_zst(&archive[..]).unwrap();
+    assert!(fs.contains("index.html"));
+    assert!(fs.contains("assets/js/app.js"));
+
+    let static_ = Static::from_memory_fs(fs);
+    let res = serve(&static_, get("/assets/js/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+    // 修改时间来自归档
+    assert_eq!(
+        res.header(header::LAST_MODIFIED),
+        Some("Tue, 14 Nov 2023 22:13:20 GMT")
+    );
+
+    let res = serve(&static_, get("/").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, INDEX_HTML);
+}
+
+#[test]
+fn invalid_archive_is_an_error() {
+    assert!(MemoryFs::from_tar_zst(&b"not zstd"[..]).is_err());
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{get, mtime, serve};
+use http::{header, Request, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+fn static_() -> Static<MemoryFs> {
+    let mut fs = MemoryFs::default();
+    for path in [
+        "list/b.txt",
+        "list/zeta/x.txt",
+        "list/a.txt",
+        "list/alpha/x.txt",
+    ] {
+        fs.add(path, Bytes::from_static(b"12345"), Some(mtime()));
+    }
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.autoindex(true);
+    static_
+}
+
+fn names(listing: &str) -> Vec<&str> {
+    listing
+        .split("\"name\":\"")
+        .skip(1)
+        .map(|rest| &rest[..rest.find('"').unwrap()])
+        .collect()
+}
+
+#[tokio::test]
+async fn json_listing_uses_configured_content_type() {
+    let mut static_ = static_();
+    static_.autoindex_json(Some("application/vnd.myapp.dir+json".to_string()));
+    let res = serve(&static_, get("/list/").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("application/vnd.myapp.dir+json")
+    );
+    let listing = std::str::from_utf8(&res.body).unwrap();
+    assert!(listing.starts_with('[') && listing.ends_with(']'));
+    // 目录在前，同类按名称排序
+    assert_eq!(names(listing), ["alpha", "zeta", "a.txt", "b.txt"]);
+    let modified = httpdate::fmt_http_date(mtime());
+    assert!(listing.contains(&format!(
+        "{{\"name\":\"a.txt\",\"is_dir\":false,\"size\":5,\"modified\":\"{modified}\"}}"
+    )));
+
+    let req = Request::head("/list/").body(()).unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(
+        res.header(header::CONTENT_LENGTH),
+        Some(listing.len().to_string().as_str())
+    );
+}
+
+#[tokio::test]
+async fn html_listing_by_default() {
+    let res = serve(&static_(), get("/list/").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("text/html; charset=utf-8")
+    );
+    let html = std::str::from_utf8(&res.body).unwrap();
+    let position = |name: &str| html.find(&format!(">{name}<")).unwrap();
+    assert!(position("alpha/") < position("zeta/"));
+    assert!(position("zeta/") < position("a.txt"));
+    assert!(position("a.txt") < position("b.txt"));
+}
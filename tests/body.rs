@@ -0,0 +1,101 @@
+mod common;
+
+use std::io::Cursor;
+
+use common::{get, memory_fs, mtime};
+use http::{header, Request, Response};
+use http_body_util::BodyExt;
+use hyper::body::{Body as _, Bytes};
+use hyper_staticfile::{vfs::MemoryFs, Body, Static};
+
+const MIB: usize = 1024 * 1024;
+
+fn static_() -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    let large: Vec<u8> = (0..MIB).map(|i| i as u8).collect();
+    fs.add("large.bin", Bytes::from(large), Some(mtime()));
+    Static::from_memory_fs(fs)
+}
+
+async fn response(static_: &Static<MemoryFs>, req: Request<()>) -> Response<Body<Cursor<Bytes>>> {
+    static_.clone().serve(req).await.unwrap()
+}
+
+/// 读完响应体，返回帧数和总长度
+async fn count_frames(body: Body<Cursor<Bytes>>) -> (usize, usize) {
+    let mut body = body;
+    let (mut frames, mut len) = (0, 0);
+    while let Some(frame) = body.frame().await {
+        frames += 1;
+        len += frame.unwrap().into_data().unwrap().len();
+    }
+    (frames, len)
+}
+
+#[tokio::test]
+async fn larger_chunk_size_means_fewer_frames() {
+    let mut static_ = static_();
+    let mut counts = Vec::new();
+    for chunk_size in [16 * 1024, 256 * 1024] {
+        static_.chunk_size(Some(chunk_size));
+        let req = get("/large.bin").body(()).unwrap();
+        let (frames, len) = count_frames(response(&static_, req).await.into_body()).await;
+        assert_eq!(len, MIB);
+        counts.push(frames);
+    }
+    assert_eq!(counts, [64, 4]);
+}
+
+/// 检查响应体的大小提示是确切值，并且与实际长度一致
+async fn assert_exact_size_hint(res: Response<Body<Cursor<Bytes>>>) {
+    let size = res.body().size_hint().exact();
+    let advertised = res
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .map(|v| v.to_str().unwrap().parse::<u64>().unwrap());
+    let (_, len) = count_frames(res.into_body()).await;
+    assert_eq!(size, Some(len as u64));
+    if let Some(advertised) = advertised {
+        assert_eq!(size, Some(advertised));
+    }
+}
+
+#[tokio::test]
+async fn full_size_hint_is_exact() {
+    let res = response(&static_(), get("/large.bin").body(()).unwrap()).await;
+    assert!(matches!(res.body(), Body::Full(_)));
+    assert_exact_size_hint(res).await;
+}
+
+#[tokio::test]
+async fn range_size_hint_is_exact() {
+    let req = get("/large.bin")
+        .header(header::RANGE, "bytes=100-1099")
+        .body(())
+        .unwrap();
+    let res = response(&static_(), req).await;
+    assert!(matches!(res.body(), Body::Range(_)));
+    assert_eq!(res.body().size_hint().exact(), Some(1000));
+    assert_exact_size_hint(res).await;
+}
+
+#[tokio::test]
+async fn multi_range_size_hint_is_exact() {
+    let req = get("/large.bin")
+        .header(header::RANGE, "bytes=0-9,100-199,-5")
+        .body(())
+        .unwrap();
+    let res = response(&static_(), req).await;
+    assert!(matches!(res.body(), Body::MultiRange(_)));
+    assert_exact_size_hint(res).await;
+}
+
+#[tokio::test]
+async fn empty_size_hint_is_exact() {
+    let req = Request::head("/large.bin").body(()).unwrap();
+    let res = response(&static_(), req).await;
+    assert!(matches!(res.body(), Body::Empty));
+    assert_eq!(res.body().size_hint().exact(), Some(0));
+    // HEAD的Content-Length是文件大小，响应体本身为空
+    assert_eq!(count_frames(res.into_body()).await, (0, 0));
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::{get, memory_fs, serve, APP_JS, INDEX_HTML};
+use http::{header, StatusCode};
+use hyper_staticfile::{util::BodyWrapper, vfs::MemoryFs, Static};
+
+const PREFIX: &[u8] = b"<!-- header -->";
+const SUFFIX: &[u8] = b"<script src=\"/snippet.js\"></script>";
+
+fn static_(mime_type: Option<&str>) -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.body_wrapper(Some(
+        BodyWrapper::new(PREFIX, SUFFIX)
+            .mime_type(mime_type.map(str::to_string))
+            .clone(),
+    ));
+    static_
+}
+
+#[tokio::test]
+async fn body_is_prefix_file_suffix() {
+    let res = serve(&static_(None), get("/index.html").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, [PREFIX, INDEX_HTML, SUFFIX].concat());
+    assert_eq!(
+        res.header(header::CONTENT_LENGTH),
+        Some(
+            (PREFIX.len() + INDEX_HTML.len() + SUFFIX.len())
+                .to_string()
+                .as_str()
+        )
+    );
+}
+
+#[tokio::test]
+async fn wrapped_body_ignores_range() {
+    let req = get("/index.html")
+        .header(header::RANGE, "bytes=0-3")
+        .body(())
+        .unwrap();
+    let res = serve(&static_(None), req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::ACCEPT_RANGES), Some("none"));
+    assert_eq!(res.body, [PREFIX, INDEX_HTML, SUFFIX].concat());
+}
+
+#[tokio::test]
+async fn only_matching_type_is_wrapped() {
+    let static_ = static_(Some("text/html"));
+    let res = serve(&static_, get("/index.html").body(()).unwrap()).await;
+    assert_eq!(res.body, [PREFIX, INDEX_HTML, SUFFIX].concat());
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.body, APP_JS);
+}
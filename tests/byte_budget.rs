@@ -0,0 +1,55 @@
+use std::{
+    io::Cursor,
+    task::{Context, Poll},
+};
+
+use futures_util::{task::noop_waker_ref, StreamExt};
+use hyper::body::Bytes;
+use hyper_staticfile::util::{ByteBudget, FileBytesStream};
+
+fn stream(data: &'static [u8], budget: &ByteBudget) -> FileBytesStream<Cursor<Bytes>> {
+    let mut stream = FileBytesStream::new(Cursor::new(Bytes::from_static(data)));
+    stream.set_byte_budget(Some(budget.clone()));
+    stream
+}
+
+fn poll(stream: &mut FileBytesStream<Cursor<Bytes>>) -> Poll<Option<Bytes>> {
+    let mut cx = Context::from_waker(noop_waker_ref());
+    stream
+        .poll_next_unpin(&mut cx)
+        .map(|item| item.map(|chunk| chunk.expect("read failed")))
+}
+
+#[test]
+fn second_stream_waits_for_released_bytes() {
+    // 预算只够一块数据在途
+    let budget = ByteBudget::new(4);
+    let mut first = stream(b"aaaaaaaa", &budget);
+    let mut second = stream(b"bbbbbbbb", &budget);
+
+    assert_eq!(
+        poll(&mut first),
+        Poll::Ready(Some(Bytes::from_static(b"aaaa")))
+    );
+    assert_eq!(budget.available_bytes(), 0);
+    // 第一块数据还没被消费，第二个流拿不到额度
+    assert_eq!(poll(&mut second), Poll::Pending);
+    assert_eq!(poll(&mut second), Poll::Pending);
+
+    // 再次poll第一个流时归还额度，排在前面的第二个流先拿到
+    assert_eq!(poll(&mut first), Poll::Pending);
+    assert_eq!(
+        poll(&mut second),
+        Poll::Ready(Some(Bytes::from_static(b"bbbb")))
+    );
+    assert_eq!(poll(&mut first), Poll::Pending);
+
+    // 丢弃第二个流同样归还额度
+    drop(second);
+    assert_eq!(
+        poll(&mut first),
+        Poll::Ready(Some(Bytes::from_static(b"aaaa")))
+    );
+    drop(first);
+    assert_eq!(budget.available_bytes(), 4);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::{get, memory_fs, serve, APP_JS};
+use http::{header, StatusCode};
+use hyper_staticfile::{util::CacheControl, vfs::MemoryFs, Static};
+
+fn static_() -> Static<MemoryFs> {
+    Static::from_memory_fs(memory_fs())
+}
+
+async fn cache_control(static_: &Static<MemoryFs>) -> Option<String> {
+    let res = serve(static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    res.header(header::CACHE_CONTROL).map(str::to_string)
+}
+
+#[tokio::test]
+async fn default_applies_without_specific_policy() {
+    let mut static_ = static_();
+    assert_eq!(cache_control(&static_).await, None);
+
+    static_.default_cache_control(Some("public, max-age=300".to_string()));
+    assert_eq!(
+        cache_control(&static_).await.as_deref(),
+        Some("public, max-age=300")
+    );
+}
+
+#[tokio::test]
+async fn specific_policy_overrides_default() {
+    let mut static_ = static_();
+    static_.default_cache_control(Some("public, max-age=300".to_string()));
+
+    static_.cache_headers(Some(60));
+    assert_eq!(
+        cache_control(&static_).await.as_deref(),
+        Some("public, max-age=60")
+    );
+
+    static_.cache_control(Some(CacheControl::new().no_cache(true).clone()));
+    assert_eq!(
+        cache_control(&static_).await.as_deref(),
+        Some("public, no-cache")
+    );
+}
+
+#[tokio::test]
+async fn stale_directives() {
+    let mut static_ = static_();
+    static_.cache_control(Some(
+        CacheControl::new()
+            .max_age(60)
+            .stale_while_revalidate(30)
+            .stale_if_error(120)
+            .clone(),
+    ));
+    assert_eq!(
+        cache_control(&static_).await.as_deref(),
+        Some("public, max-age=60, stale-while-revalidate=30, stale-if-error=120")
+    );
+}
+
+#[tokio::test]
+async fn no_store_drops_validators() {
+    let etag = serve(&static_(), get("/app.js").body(()).unwrap())
+        .await
+        .header(header::ETAG)
+        .unwrap()
+        .to_string();
+
+    let mut static_ = static_();
+    static_.cache_control(Some(CacheControl::new().no_store(true).clone()));
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::ETAG), None);
+    assert_eq!(res.header(header::LAST_MODIFIED), None);
+
+    // 条件请求被忽略
+    let req = get("/app.js")
+        .header(header::IF_NONE_MATCH, etag)
+        .header(header::IF_MODIFIED_SINCE, "Tue, 14 Nov 2023 22:13:20 GMT")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+}
+
+#[test]
+fn directive_combinations() {
+    for (cache_control, expected) in [
+        (CacheControl::new(), "public"),
+        (
+            CacheControl::new().private(true).max_age(60).clone(),
+            "private, max-age=60",
+        ),
+        (
+            CacheControl::new().private(true).no_cache(true).clone(),
+            "private, no-cache",
+        ),
+        (
+            CacheControl::new()
+                .max_age(31536000)
+                .immutable(true)
+                .clone(),
+            "public, max-age=31536000, immutable",
+        ),
+        // no-store时其它指令没有意义
+        (
+            CacheControl::new()
+                .private(true)
+                .max_age(60)
+                .immutable(true)
+                .no_store(true)
+                .clone(),
+            "no-store",
+        ),
+    ] {
+        assert_eq!(cache_control.to_string(), expected);
+    }
+}
+
+#[tokio::test]
+async fn policy_is_sent_on_responses() {
+    let mut static_ = static_();
+    static_.cache_control(Some(
+        CacheControl::new().private(true).no_cache(true).clone(),
+    ));
+    assert_eq!(
+        cache_control(&static_).await.as_deref(),
+        Some("private, no-cache")
+    );
+}
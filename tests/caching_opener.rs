@@ -0,0 +1,175 @@
+mod common;
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "digest")]
+use common::{get, serve};
+use common::{memory_fs, mtime, APP_JS, INDEX_HTML};
+use futures_util::StreamExt;
+#[cfg(feature = "digest")]
+use http::header;
+use hyper::body::Bytes;
+#[cfg(feature = "digest")]
+use hyper_staticfile::Static;
+use hyper_staticfile::{
+    util::FileBytesStream,
+    vfs::{CachingOpener, FileOpener, IntoFileAccess, MemoryFs},
+};
+
+/// 记录打开次数，内容可以在测试中修改
+#[derive(Clone)]
+struct CountingFs {
+    fs: Arc<Mutex<MemoryFs>>,
+    opens: Arc<AtomicUsize>,
+}
+
+impl CountingFs {
+    fn new() -> Self {
+        Self {
+            fs: Arc::new(Mutex::new(memory_fs())),
+            opens: Arc::default(),
+        }
+    }
+
+    fn opens(&self) -> usize {
+        self.opens.load(Ordering::Relaxed)
+    }
+}
+
+impl FileOpener for CountingFs {
+    type File = <MemoryFs as FileOpener>::File;
+    type Future = <MemoryFs as FileOpener>::Future;
+
+    fn open(&self, path: &Path) -> Self::Future {
+        self.opens.fetch_add(1, Ordering::Relaxed);
+        self.fs.lock().unwrap().open(path)
+    }
+}
+
+fn caching(fs: &CountingFs, max_bytes: u64) -> CachingOpener<CountingFs> {
+    let mut opener = CachingOpener::new(fs.clone(), max_bytes);
+    opener.revalidate_after(Duration::from_secs(60));
+    opener
+}
+
+async fn read(opener: &CachingOpener<CountingFs>, path: &str) -> Bytes {
+    let file = opener.open(Path::new(path)).await.unwrap();
+    let mut stream = FileBytesStream::new(file.handle.into_file_access());
+    let mut data = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        data.extend_from_slice(&chunk.unwrap());
+    }
+    data.into()
+}
+
+#[tokio::test]
+async fn hits_skip_inner_opener() {
+    let fs = CountingFs::new();
+    let opener = caching(&fs, 1024);
+    assert_eq!(read(&opener, "app.js").await, APP_JS);
+    assert_eq!(fs.opens(), 1);
+    assert_eq!(opener.cached_bytes(), APP_JS.len() as u64);
+
+    for _ in 0..3 {
+        assert_eq!(read(&opener, "app.js").await, APP_JS);
+    }
+    assert_eq!(fs.opens(), 1);
+}
+
+#[tokio::test]
+async fn least_recently_used_is_evicted() {
+    let fs = CountingFs::new();
+    fs.fs
+        .lock()
+        .unwrap()
+        .add("extra.txt", Bytes::from_static(&[b'x'; 20]), Some(mtime()));
+    let budget = (INDEX_HTML.len() + APP_JS.len()) as u64 + 10;
+    let opener = caching(&fs, budget);
+
+    read(&opener, "index.html").await;
+    read(&opener, "app.js").await;
+    // index.html成为最近使用的条目
+    read(&opener, "index.html").await;
+    assert_eq!(fs.opens(), 2);
+
+    // 放不下时淘汰app.js
+    read(&opener, "extra.txt").await;
+    assert_eq!(opener.cached_bytes(), (INDEX_HTML.len() + 20) as u64);
+    read(&opener, "index.html").await;
+    assert_eq!(fs.opens(), 3);
+    assert_eq!(read(&opener, "app.js").await, APP_JS);
+    assert_eq!(fs.opens(), 4);
+
+    // 超出预算的文件不缓存
+    read(&opener, "data.bin").await;
+    read(&opener, "data.bin").await;
+    assert_eq!(fs.opens(), 6);
+    assert!(opener.cached_bytes() <= budget);
+}
+
+#[tokio::test]
+async fn revalidate_after_controls_staleness() {
+    let fs = CountingFs::new();
+    let mut opener = caching(&fs, 1024);
+    read(&opener, "app.js").await;
+
+    // 修改时间变化，但还没到重新检查的时间
+    let changed = Bytes::from_static(b"changed");
+    let later = mtime() + Duration::from_secs(1);
+    fs.fs
+        .lock()
+        .unwrap()
+        .add("app.js", changed.clone(), Some(later));
+    assert_eq!(read(&opener, "app.js").await, APP_JS);
+    assert_eq!(fs.opens(), 1);
+
+    // 每次都重新检查，发现变化后重新读取
+    opener.revalidate_after(Duration::ZERO);
+    assert_eq!(read(&opener, "app.js").await, changed);
+    assert_eq!(fs.opens(), 2);
+    // 没有变化时打开内部文件确认，但内容仍来自缓存
+    assert_eq!(read(&opener, "app.js").await, changed);
+    assert_eq!(fs.opens(), 3);
+    assert_eq!(opener.cached_bytes(), changed.len() as u64);
+}
+
+#[cfg(feature = "digest")]
+#[tokio::test]
+async fn strong_etag_is_cached_between_requests() {
+    let fs = CountingFs::new();
+    fs.fs
+        .lock()
+        .unwrap()
+        .add("no-mtime.txt", Bytes::from_static(b"no mtime"), None);
+    let mut static_ = Static::with_opener(fs.clone());
+    static_.strong_etag(true);
+
+    for path in ["/app.js", "/no-mtime.txt"] {
+        // 第一次请求额外打开一次文件计算ETag
+        let before = fs.opens();
+        let first = serve(&static_, get(path).body(()).unwrap()).await;
+        let first_opens = fs.opens() - before;
+        let etag = first.header(header::ETAG).unwrap().to_owned();
+        assert!(!etag.starts_with("W/"));
+
+        // 内容没有变化时直接使用缓存的ETag
+        let before = fs.opens();
+        let second = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(fs.opens() - before, first_opens - 1, "{path}");
+        assert_eq!(second.header(header::ETAG), Some(etag.as_str()));
+    }
+
+    // 修改时间变化后重新计算
+    let later = mtime() + Duration::from_secs(1);
+    fs.fs
+        .lock()
+        .unwrap()
+        .add("app.js", Bytes::from_static(b"changed"), Some(later));
+    let before = fs.opens();
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(fs.opens() - before, 2);
+    assert_eq!(res.body, &b"changed"[..]);
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use std::collections::HashMap;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use common::{get, memory_fs, mtime, serve, APP_JS, INDEX_HTML};
+use futures_util::future::BoxFuture;
+use http::{header, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{
+    vfs::{DirectoryEntry, FileOpener, MemoryFs},
+    Static,
+};
+
+/// 模拟不区分大小写的文件系统，按小写路径打开实际的文件
+#[derive(Clone)]
+struct CaseInsensitiveFs {
+    fs: Arc<MemoryFs>,
+    lower: Arc<HashMap<PathBuf, PathBuf>>,
+}
+
+impl CaseInsensitiveFs {
+    fn new(fs: MemoryFs) -> Self {
+        let mut lower = HashMap::new();
+        for path in fs.paths() {
+            for path in path.ancestors() {
+                lower.insert(lowercase(path), path.to_owned());
+            }
+        }
+        Self {
+            fs: Arc::new(fs),
+            lower: Arc::new(lower),
+        }
+    }
+}
+
+fn lowercase(path: &Path) -> PathBuf {
+    path.to_str().unwrap().to_lowercase().into()
+}
+
+impl FileOpener for CaseInsensitiveFs {
+    type File = <MemoryFs as FileOpener>::File;
+    type Future = <MemoryFs as FileOpener>::Future;
+
+    fn open(&self, path: &Path) -> Self::Future {
+        match self.lower.get(&lowercase(path)) {
+            Some(actual) => self.fs.open(actual),
+            None => self.fs.open(path),
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> BoxFuture<'static, Result<Vec<DirectoryEntry>, Error>> {
+        self.fs.read_dir(path)
+    }
+}
+
+fn static_() -> Static<CaseInsensitiveFs> {
+    let mut fs = memory_fs();
+    fs.add("Readme.txt", Bytes::from_static(b"readme"), Some(mtime()));
+    let mut static_ = Static::with_opener(CaseInsensitiveFs::new(fs));
+    static_.canonical_case_redirect(true);
+    static_
+}
+
+#[tokio::test]
+async fn wrong_case_redirects_to_disk_casing() {
+    let static_ = static_();
+    for (path, location) in [
+        ("/APP.JS", "/app.js"),
+        ("/readme.TXT", "/Readme.txt"),
+        ("/DOCS/", "/docs/"),
+        ("/Docs/Index.html", "/docs/index.html"),
+    ] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY, "{path}");
+        assert_eq!(res.header(header::LOCATION), Some(location), "{path}");
+    }
+}
+
+#[tokio::test]
+async fn exact_case_is_served() {
+    let static_ = static_();
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+
+    let res = serve(&static_, get("/docs/").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, INDEX_HTML);
+}
+
+#[tokio::test]
+async fn disabled_serves_any_case() {
+    let mut static_ = static_();
+    static_.canonical_case_redirect(false);
+    let res = serve(&static_, get("/APP.JS").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::LOCATION), None);
+    assert_eq!(res.body, APP_JS);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::{get, memory_fs, mtime, serve};
+use http::{header, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+const PAGE: &[u8] = b"<h1>page</h1>";
+
+fn static_(clean_urls: bool) -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add("page.html", Bytes::from_static(PAGE), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.clean_urls(clean_urls);
+    static_
+}
+
+#[tokio::test]
+async fn html_extension_redirects_to_clean_url() {
+    let static_ = static_(true);
+    let res = serve(&static_, get("/page.html").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.header(header::LOCATION), Some("/page"));
+
+    // 重定向的目标直接返回内容，不会再次重定向
+    let res = serve(&static_, get("/page").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::LOCATION), None);
+    assert_eq!(res.body, PAGE);
+}
+
+#[tokio::test]
+async fn html_extension_served_when_disabled() {
+    let static_ = static_(false);
+    let res = serve(&static_, get("/page.html").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, PAGE);
+    let res = serve(&static_, get("/page").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,133 @@
+//! 集成测试共用的工具
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use http::{header, response::Parts, HeaderMap, Request, StatusCode};
+use http_body_util::BodyExt;
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::FileOpener, vfs::MemoryFs, Static};
+
+/// 测试文件的修改时间
+pub const MTIME_SECS: u64 = 1_700_000_000;
+
+pub const INDEX_HTML: &[u8] = b"<h1>index</h1>";
+pub const APP_JS: &[u8] = b"console.log('hello world');\n";
+
+pub fn mtime() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(MTIME_SECS)
+}
+
+/// 0到255循环的1024字节，便于检查范围请求的内容
+pub fn data_bin() -> Bytes {
+    (0..1024).map(|i| i as u8).collect::<Vec<u8>>().into()
+}
+
+pub fn memory_fs() -> MemoryFs {
+    let mut fs = MemoryFs::default();
+    fs.add("index.html", Bytes::from_static(INDEX_HTML), Some(mtime()));
+    fs.add("app.js", Bytes::from_static(APP_JS), Some(mtime()));
+    fs.add("data.bin", data_bin(), Some(mtime()));
+    fs.add(
+        "docs/index.html",
+        Bytes::from_static(INDEX_HTML),
+        Some(mtime()),
+    );
+    fs
+}
+
+pub fn get(uri: &str) -> http::request::Builder {
+    Request::get(uri)
+}
+
+/// 读取完的响应
+pub struct TestResponse {
+    pub parts: Parts,
+    pub body: Bytes,
+    pub trailers: Option<HeaderMap>,
+}
+
+impl TestResponse {
+    pub fn status(&self) -> StatusCode {
+        self.parts.status
+    }
+
+    pub fn header(&self, name: impl header::AsHeaderName) -> Option<&str> {
+        self.parts
+            .headers
+            .get(name)
+            .map(|v| v.to_str().expect("header is not ASCII"))
+    }
+}
+
+/// 发送请求并读取完整响应，同时检查Content-Length与实际输出的字节数一致
+pub async fn serve<O: FileOpener>(static_: &Static<O>, request: Request<()>) -> TestResponse {
+    let is_head = request.method() == http::Method::HEAD;
+    let response = static_.clone().serve(request).await.expect("serve failed");
+    let (parts, body) = response.into_parts();
+    let collected = body.collect().await.expect("body failed");
+    let trailers = collected.trailers().cloned();
+    let body = collected.to_bytes();
+    check_content_length(&parts, &body, is_head);
+    TestResponse {
+        parts,
+        body,
+        trailers,
+    }
+}
+
+/// HEAD和304的响应体必须为空，其它响应带Content-Length时必须与响应体长度一致
+pub fn check_content_length(parts: &Parts, body: &[u8], is_head: bool) {
+    if is_head || parts.status == StatusCode::NOT_MODIFIED {
+        assert!(body.is_empty(), "{} response has a body", parts.status);
+        return;
+    }
+    if let Some(value) = parts.headers.get(header::CONTENT_LENGTH) {
+        let advertised: u64 = value
+            .to_str()
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .expect("invalid Content-Length");
+        assert_eq!(
+            advertised,
+            body.len() as u64,
+            "Content-Length does not match the body of a {} response",
+            parts.status
+        );
+    }
+}
+
+/// 系统临时目录下的独立目录，离开作用域时删除
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "hyper-staticfile-{name}-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path).expect("unable to create temp dir");
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// 在目录下写入文件，自动创建上级目录
+    pub fn write(&self, path: &str, data: &[u8]) {
+        let path = self.0.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
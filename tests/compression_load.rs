@@ -0,0 +1,78 @@
+#![cfg(feature = "compression")]
+
+mod common;
+
+use common::{get, memory_fs, serve, APP_JS};
+use http::{header, Request, StatusCode};
+use hyper_staticfile::{
+    util::{CompressionLoad, CompressionQuality},
+    vfs::MemoryFs,
+    AcceptEncoding, Static,
+};
+
+fn static_(load: &CompressionLoad) -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_
+        .allowed_encodings(AcceptEncoding::all())
+        .compress_on_the_fly(AcceptEncoding::all())
+        .compression_load(Some(load.clone()));
+    static_
+}
+
+fn gzip() -> Request<()> {
+    get("/app.js")
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .body(())
+        .unwrap()
+}
+
+/// gzip头中的XFL：0为默认级别，4为最快级别
+fn gzip_xfl(body: &[u8]) -> u8 {
+    assert_eq!(&body[..2], [0x1f, 0x8b], "not a gzip stream");
+    body[8]
+}
+
+#[tokio::test]
+async fn quality_drops_with_in_flight_compressions() {
+    let load = CompressionLoad::new(1, 2);
+    let static_ = static_(&load);
+
+    assert_eq!(load.quality(), CompressionQuality::Default);
+    let res = serve(&static_, gzip()).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(gzip_xfl(&res.body), 0);
+    assert_eq!(load.in_flight(), 0);
+
+    // 未读完的响应体仍在压缩中
+    let first = static_.clone().serve(gzip()).await.unwrap();
+    assert_eq!(load.in_flight(), 1);
+    assert_eq!(load.quality(), CompressionQuality::Fast);
+    let res = serve(&static_, gzip()).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(gzip_xfl(&res.body), 4);
+
+    let second = static_.clone().serve(gzip()).await.unwrap();
+    assert_eq!(load.in_flight(), 2);
+    assert_eq!(load.quality(), CompressionQuality::Off);
+    let res = serve(&static_, gzip()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), None);
+    assert_eq!(res.body, APP_JS);
+    assert_eq!(load.in_flight(), 2);
+
+    drop((first, second));
+    assert_eq!(load.in_flight(), 0);
+    let res = serve(&static_, gzip()).await;
+    assert_eq!(gzip_xfl(&res.body), 0);
+}
+
+#[tokio::test]
+async fn load_is_shared_between_services() {
+    let load = CompressionLoad::new(1, 1);
+    let held = static_(&load).serve(gzip()).await.unwrap();
+    assert_eq!(held.headers()[header::CONTENT_ENCODING], "gzip");
+
+    let res = serve(&static_(&load), gzip()).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), None);
+    assert_eq!(res.body, APP_JS);
+}
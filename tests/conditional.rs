@@ -0,0 +1,255 @@
+mod common;
+
+use std::time::{Duration, SystemTime};
+
+use common::{get, memory_fs, mtime, serve, APP_JS};
+use http::{header, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+fn static_() -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add("no-mtime.txt", Bytes::from_static(b"no mtime"), None);
+    fs.add(
+        "subsec.txt",
+        Bytes::from_static(b"sub-second mtime"),
+        Some(mtime() + Duration::from_millis(500)),
+    );
+    Static::from_memory_fs(fs)
+}
+
+async fn etag_of(static_: &Static<MemoryFs>, path: &str) -> String {
+    let res = serve(static_, get(path).body(()).unwrap()).await;
+    res.header(header::ETAG).expect("missing ETag").to_string()
+}
+
+#[tokio::test]
+async fn if_match_star_matches_existing_file() {
+    let req = get("/app.js")
+        .header(header::IF_MATCH, "*")
+        .body(())
+        .unwrap();
+    let res = serve(&static_(), req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+}
+
+#[tokio::test]
+async fn if_match_never_matches_weak_etag() {
+    let static_ = static_();
+    let etag = etag_of(&static_, "/app.js").await;
+    assert!(etag.starts_with("W/"));
+    let req = get("/app.js")
+        .header(header::IF_MATCH, etag)
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+    assert!(res.body.is_empty());
+}
+
+#[tokio::test]
+async fn if_match_mismatch_fails() {
+    let req = get("/app.js")
+        .header(header::IF_MATCH, "\"something-else\"")
+        .body(())
+        .unwrap();
+    let res = serve(&static_(), req).await;
+    assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+    assert!(res.body.is_empty());
+}
+
+#[tokio::test]
+async fn if_match_without_mtime() {
+    let static_ = static_();
+    let res = serve(&static_, get("/no-mtime.txt").body(()).unwrap()).await;
+    assert_eq!(res.header(header::ETAG), None);
+
+    let req = get("/no-mtime.txt")
+        .header(header::IF_MATCH, "\"anything\"")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+    let req = get("/no-mtime.txt")
+        .header(header::IF_MATCH, "*")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[cfg(feature = "digest")]
+#[tokio::test]
+async fn if_match_strong_etag_without_mtime() {
+    // 强ETag只依赖内容，没有修改时间时同样能匹配
+    let mut static_ = static_();
+    static_.strong_etag(true);
+    let etag = etag_of(&static_, "/no-mtime.txt").await;
+    assert!(!etag.starts_with("W/"));
+
+    let req = get("/no-mtime.txt")
+        .header(header::IF_MATCH, etag)
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, &b"no mtime"[..]);
+
+    let req = get("/no-mtime.txt")
+        .header(header::IF_MATCH, "\"anything\"")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+}
+
+#[cfg(feature = "digest")]
+#[tokio::test]
+async fn if_match_strong_etag() {
+    let mut static_ = static_();
+    static_.strong_etag(true);
+    let etag = etag_of(&static_, "/app.js").await;
+    assert!(!etag.starts_with("W/"));
+
+    let req = get("/app.js")
+        .header(header::IF_MATCH, format!("\"other\", {etag}"))
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let req = get("/app.js")
+        .header(header::IF_MATCH, format!("W/{etag}"))
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+}
+
+async fn if_modified_since(path: &str, time: SystemTime) -> StatusCode {
+    let req = get(path)
+        .header(header::IF_MODIFIED_SINCE, httpdate::fmt_http_date(time))
+        .body(())
+        .unwrap();
+    serve(&static_(), req).await.status()
+}
+
+#[tokio::test]
+async fn if_modified_since_file_modified_before() {
+    let later = mtime() + Duration::from_secs(60);
+    assert_eq!(
+        if_modified_since("/app.js", later).await,
+        StatusCode::NOT_MODIFIED
+    );
+}
+
+#[tokio::test]
+async fn if_modified_since_file_modified_after() {
+    let earlier = mtime() - Duration::from_secs(1);
+    let req = get("/app.js")
+        .header(header::IF_MODIFIED_SINCE, httpdate::fmt_http_date(earlier))
+        .body(())
+        .unwrap();
+    let res = serve(&static_(), req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+}
+
+#[tokio::test]
+async fn if_modified_since_equal_second() {
+    assert_eq!(
+        if_modified_since("/app.js", mtime()).await,
+        StatusCode::NOT_MODIFIED
+    );
+    // HTTP日期只精确到秒，同一秒内的亚秒修改时间不算修改过
+    assert_eq!(
+        if_modified_since("/subsec.txt", mtime()).await,
+        StatusCode::NOT_MODIFIED
+    );
+}
+
+#[tokio::test]
+async fn if_none_match_takes_precedence_over_if_modified_since() {
+    let mut static_ = static_();
+    static_.default_cache_control(Some("public, max-age=60".to_string()));
+    let etag = etag_of(&static_, "/app.js").await;
+    let later = httpdate::fmt_http_date(mtime() + Duration::from_secs(60));
+    let earlier = httpdate::fmt_http_date(mtime() - Duration::from_secs(60));
+
+    // ETag匹配时即使If-Modified-Since表示已修改也返回304
+    let req = get("/app.js")
+        .header(header::IF_NONE_MATCH, &etag)
+        .header(header::IF_MODIFIED_SINCE, &earlier)
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(res.header(header::ETAG), Some(etag.as_str()));
+    assert_eq!(
+        res.header(header::CACHE_CONTROL),
+        Some("public, max-age=60")
+    );
+
+    // ETag不匹配时忽略If-Modified-Since
+    let req = get("/app.js")
+        .header(header::IF_NONE_MATCH, "\"other\"")
+        .header(header::IF_MODIFIED_SINCE, &later)
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+}
+
+#[tokio::test]
+async fn if_none_match_list_and_star() {
+    let static_ = static_();
+    let etag = etag_of(&static_, "/app.js").await;
+    for value in [format!("\"other\", {etag}"), "*".to_string()] {
+        let req = get("/app.js")
+            .header(header::IF_NONE_MATCH, value)
+            .body(())
+            .unwrap();
+        let res = serve(&static_, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.header(header::ETAG), Some(etag.as_str()));
+    }
+}
+
+#[tokio::test]
+async fn validator_matrix() {
+    let static_ = static_();
+    let etag = etag_of(&static_, "/app.js").await;
+    let matching_ims = httpdate::fmt_http_date(mtime());
+    let stale_ims = httpdate::fmt_http_date(mtime() - Duration::from_secs(60));
+    let cases = [
+        (None, None, StatusCode::OK),
+        (Some(&matching_ims), None, StatusCode::NOT_MODIFIED),
+        (Some(&stale_ims), None, StatusCode::OK),
+        (None, Some(etag.as_str()), StatusCode::NOT_MODIFIED),
+        (None, Some("\"other\""), StatusCode::OK),
+        // 两者结果冲突时以If-None-Match为准
+        (
+            Some(&stale_ims),
+            Some(etag.as_str()),
+            StatusCode::NOT_MODIFIED,
+        ),
+        (Some(&matching_ims), Some("\"other\""), StatusCode::OK),
+    ];
+    for (ims, inm, expected) in cases {
+        let mut req = get("/app.js");
+        if let Some(ims) = ims {
+            req = req.header(header::IF_MODIFIED_SINCE, ims);
+        }
+        if let Some(inm) = inm {
+            req = req.header(header::IF_NONE_MATCH, inm);
+        }
+        let res = serve(&static_, req.body(()).unwrap()).await;
+        assert_eq!(res.status(), expected, "IMS {ims:?}, INM {inm:?}");
+        if expected == StatusCode::OK {
+            assert_eq!(res.body, APP_JS);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::{get, memory_fs, mtime, serve};
+use http::{header, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{util::ContentDisposition, vfs::MemoryFs, Static};
+
+fn static_(disposition: ContentDisposition) -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add(
+        "报告 2024.txt",
+        Bytes::from_static(b"report"),
+        Some(mtime()),
+    );
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.content_disposition(Some(disposition));
+    static_
+}
+
+#[tokio::test]
+async fn ascii_filename() {
+    for (disposition, expected) in [
+        (
+            ContentDisposition::Attachment,
+            "attachment; filename=\"app.js\"",
+        ),
+        (ContentDisposition::Inline, "inline; filename=\"app.js\""),
+    ] {
+        let res = serve(&static_(disposition), get("/app.js").body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.header(header::CONTENT_DISPOSITION), Some(expected));
+    }
+}
+
+#[tokio::test]
+async fn unicode_filename() {
+    let req = get("/%E6%8A%A5%E5%91%8A%202024.txt").body(()).unwrap();
+    let res = serve(&static_(ContentDisposition::Attachment), req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.header(header::CONTENT_DISPOSITION),
+        Some(
+            "attachment; filename=\"__ 2024.txt\"; filename*=UTF-8''%E6%8A%A5%E5%91%8A%202024.txt"
+        )
+    );
+}
+
+#[test]
+fn quotes_are_escaped() {
+    assert_eq!(
+        ContentDisposition::Attachment.to_header_value("a\"b\\c.txt"),
+        "attachment; filename=\"a\\\"b\\\\c.txt\""
+    );
+}
+
+#[tokio::test]
+async fn no_header_by_default() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.header(header::CONTENT_DISPOSITION), None);
+}
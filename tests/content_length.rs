@@ -0,0 +1,92 @@
+mod common;
+
+use common::{check_content_length, data_bin, get, memory_fs, mtime, serve, APP_JS};
+use http::{header, Request, Response, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::Static;
+
+#[tokio::test]
+async fn full_response_matches_content_length() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+}
+
+#[tokio::test]
+async fn single_range_matches_content_length() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let req = get("/data.bin")
+        .header(header::RANGE, "bytes=10-19")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(res.header(header::CONTENT_RANGE), Some("bytes 10-19/1024"));
+    assert_eq!(res.body, data_bin().slice(10..20));
+}
+
+#[tokio::test]
+async fn multipart_range_matches_content_length() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let req = get("/data.bin")
+        .header(header::RANGE, "bytes=0-9,100-199,-5")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+    assert!(res
+        .header(header::CONTENT_TYPE)
+        .unwrap()
+        .starts_with("multipart/byteranges; boundary="));
+}
+
+#[tokio::test]
+async fn head_has_no_body() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let req = Request::head("/data.bin")
+        .header(header::RANGE, "bytes=0-9,20-29")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+    assert!(res.header(header::CONTENT_LENGTH).is_some());
+}
+
+#[tokio::test]
+async fn empty_file() {
+    let mut fs = memory_fs();
+    fs.add("empty.txt", Bytes::new(), Some(mtime()));
+    let static_ = Static::from_memory_fs(fs);
+
+    let res = serve(&static_, get("/empty.txt").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_LENGTH), Some("0"));
+    assert!(res.body.is_empty());
+
+    let req = Request::head("/empty.txt").body(()).unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_LENGTH), Some("0"));
+    assert!(res.body.is_empty());
+
+    // 空文件没有可满足的范围
+    let req = get("/empty.txt")
+        .header(header::RANGE, "bytes=0-0")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(res.header(header::CONTENT_RANGE), Some("bytes */0"));
+}
+
+#[test]
+#[should_panic(expected = "Content-Length does not match")]
+fn mismatch_is_detected() {
+    let (parts, _) = Response::builder()
+        .header(header::CONTENT_LENGTH, "10")
+        .body(())
+        .unwrap()
+        .into_parts();
+    check_content_length(&parts, b"short", false);
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use common::{get, memory_fs, mtime, serve};
+use http::{header, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, AcceptEncoding, Static};
+use mime_guess::mime;
+
+const REPORT: &[u8] = b"id,name\n1,a\n";
+
+fn static_() -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add("report", Bytes::from_static(REPORT), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_
+        .resolver
+        .add_content_type_variants("/report", [mime::TEXT_CSV, mime::APPLICATION_JSON]);
+    static_
+}
+
+#[tokio::test]
+async fn accept_selects_content_type() {
+    let static_ = static_();
+    for (accept, expected) in [
+        ("text/csv", "text/csv; charset=utf-8"),
+        ("application/json", "application/json; charset=utf-8"),
+        (
+            "text/csv;q=0.5, application/json",
+            "application/json; charset=utf-8",
+        ),
+    ] {
+        let req = get("/report")
+            .header(header::ACCEPT, accept)
+            .body(())
+            .unwrap();
+        let res = serve(&static_, req).await;
+        assert_eq!(res.status(), StatusCode::OK, "{accept}");
+        assert_eq!(res.header(header::CONTENT_TYPE), Some(expected), "{accept}");
+        assert_eq!(res.header(header::VARY), Some("accept"), "{accept}");
+        // 只改变Content-Type，内容不变
+        assert_eq!(res.body, REPORT, "{accept}");
+    }
+}
+
+#[tokio::test]
+async fn first_variant_without_accept() {
+    let res = serve(&static_(), get("/report").body(()).unwrap()).await;
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("text/csv; charset=utf-8")
+    );
+    assert_eq!(res.header(header::VARY), Some("accept"));
+}
+
+#[tokio::test]
+async fn image_negotiation_varies_on_accept_and_encoding() {
+    let mut fs = memory_fs();
+    fs.add("photo", Bytes::from_static(b"image"), Some(mtime()));
+    fs.add(
+        "photo.gz",
+        Bytes::from_static(b"gzipped image"),
+        Some(mtime()),
+    );
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.allowed_encodings(AcceptEncoding::all());
+    static_.resolver.add_content_type_variants(
+        "/photo",
+        ["image/avif".parse().unwrap(), "image/webp".parse().unwrap()],
+    );
+
+    let req = get("/photo")
+        .header(header::ACCEPT, "image/webp,*/*;q=0.8")
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_TYPE), Some("image/webp"));
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    // 两个字段放在同一个Vary里
+    assert_eq!(res.parts.headers.get_all(header::VARY).iter().count(), 1);
+    let mut vary: Vec<_> = res
+        .header(header::VARY)
+        .unwrap()
+        .split(',')
+        .map(str::trim)
+        .collect();
+    vary.sort_unstable();
+    assert_eq!(vary, ["accept", "accept-encoding"]);
+}
+
+#[tokio::test]
+async fn mime_override_takes_precedence() {
+    let mut fs = memory_fs();
+    fs.add("module.wasm", Bytes::from_static(b"\0asm"), Some(mtime()));
+    fs.add("site.conf", Bytes::from_static(b"key=value"), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_
+        .resolver
+        .add_mime_override("wasm", "application/wasm".parse().unwrap())
+        .add_mime_override("CONF", "text/x-config".parse().unwrap())
+        .add_mime_override("js", "application/x-custom".parse().unwrap());
+
+    for (path, expected) in [
+        ("/module.wasm", "application/wasm"),
+        // 覆盖的文本类型同样加上charset
+        ("/site.conf", "text/x-config; charset=utf-8"),
+        // 优先于mime_guess的结果
+        ("/app.js", "application/x-custom"),
+        ("/index.html", "text/html; charset=utf-8"),
+    ] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::OK, "{path}");
+        assert_eq!(res.header(header::CONTENT_TYPE), Some(expected), "{path}");
+    }
+}
+
+#[tokio::test]
+async fn default_content_type_for_unknown_extension() {
+    let mut fs = memory_fs();
+    fs.add("LICENSE", Bytes::from_static(b"MIT"), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+
+    // 默认不设置Content-Type
+    let res = serve(&static_, get("/LICENSE").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_TYPE), None);
+
+    static_.default_content_type(Some("application/octet-stream".to_string()));
+    let res = serve(&static_, get("/LICENSE").body(()).unwrap()).await;
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("application/octet-stream")
+    );
+    // 能猜出类型的文件不受影响
+    let res = serve(&static_, get("/index.html").body(()).unwrap()).await;
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("text/html; charset=utf-8")
+    );
+}
+
+#[tokio::test]
+async fn charset_for_text_types() {
+    let mut fs = memory_fs();
+    fs.add("style.css", Bytes::from_static(b"body {}"), Some(mtime()));
+    fs.add("logo.png", Bytes::from_static(b"\x89PNG"), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+
+    for (path, expected) in [
+        ("/index.html", "text/html; charset=utf-8"),
+        ("/style.css", "text/css; charset=utf-8"),
+        ("/logo.png", "image/png"),
+    ] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.header(header::CONTENT_TYPE), Some(expected), "{path}");
+    }
+
+    static_.charset(Some("iso-8859-1".to_string()));
+    let res = serve(&static_, get("/style.css").body(()).unwrap()).await;
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("text/css; charset=iso-8859-1")
+    );
+
+    // 关闭后不附加charset
+    static_.charset(None);
+    for (path, expected) in [("/index.html", "text/html"), ("/style.css", "text/css")] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.header(header::CONTENT_TYPE), Some(expected), "{path}");
+    }
+}
+
+#[tokio::test]
+async fn magic_type_for_extensionless_file() {
+    let mut fs = memory_fs();
+    fs.add(
+        "data",
+        Bytes::from_static(b"MYFM\x01\x02payload"),
+        Some(mtime()),
+    );
+    fs.add("other", Bytes::from_static(b"MY"), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_
+        .resolver
+        .add_magic_type(*b"MYFM", "application/x-myformat".parse().unwrap());
+
+    let res = serve(&static_, get("/data").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("application/x-myformat")
+    );
+    assert_eq!(res.body, "MYFM\x01\x02payload");
+
+    // 比签名短的文件不匹配
+    let res = serve(&static_, get("/other").body(()).unwrap()).await;
+    assert_eq!(res.header(header::CONTENT_TYPE), None);
+    // 扩展名能确定类型时不受影响
+    let res = serve(&static_, get("/index.html").body(()).unwrap()).await;
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("text/html; charset=utf-8")
+    );
+}
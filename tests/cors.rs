@@ -0,0 +1,68 @@
+mod common;
+
+use common::{get, memory_fs, serve, TestResponse};
+use http::{header, Method, Request, StatusCode};
+use hyper_staticfile::{util::Cors, vfs::MemoryFs, Static};
+
+fn static_(cors: Cors) -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.cors(Some(cors));
+    static_
+}
+
+async fn with_origin(static_: &Static<MemoryFs>, origin: &str) -> TestResponse {
+    let req = get("/app.js")
+        .header(header::ORIGIN, origin)
+        .body(())
+        .unwrap();
+    let res = serve(static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    res
+}
+
+#[tokio::test]
+async fn allowed_origin_is_echoed() {
+    let static_ = static_(Cors::allow_list(["https://example.com"]));
+    let res = with_origin(&static_, "https://example.com").await;
+    assert_eq!(
+        res.header(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+        Some("https://example.com")
+    );
+    assert_eq!(res.header(header::VARY), Some("origin"));
+}
+
+#[tokio::test]
+async fn disallowed_origin_gets_no_cors_headers() {
+    let static_ = static_(Cors::allow_list(["https://example.com"]));
+    let res = with_origin(&static_, "https://evil.example").await;
+    assert_eq!(res.header(header::ACCESS_CONTROL_ALLOW_ORIGIN), None);
+    // 缓存仍需按Origin区分
+    assert_eq!(res.header(header::VARY), Some("origin"));
+}
+
+#[tokio::test]
+async fn wildcard_allows_any_origin() {
+    let static_ = static_(
+        Cors::any()
+            .allow_methods(vec![Method::GET, Method::HEAD])
+            .max_age(Some(600))
+            .clone(),
+    );
+    let res = with_origin(&static_, "https://anything.example").await;
+    assert_eq!(res.header(header::ACCESS_CONTROL_ALLOW_ORIGIN), Some("*"));
+    assert_eq!(
+        res.header(header::ACCESS_CONTROL_ALLOW_METHODS),
+        Some("GET, HEAD")
+    );
+    assert_eq!(res.header(header::ACCESS_CONTROL_MAX_AGE), Some("600"));
+    assert_eq!(res.header(header::VARY), None);
+
+    // 预检请求同样带有CORS头
+    let req = Request::options("/app.js")
+        .header(header::ORIGIN, "https://anything.example")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    assert_eq!(res.header(header::ACCESS_CONTROL_ALLOW_ORIGIN), Some("*"));
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "digest")]
+
+mod common;
+
+use common::{get, memory_fs, serve, APP_JS};
+use http::{header, StatusCode};
+use hyper_staticfile::Static;
+
+/// `APP_JS`的SHA-256
+const APP_JS_CONTENT_DIGEST: &str = "sha-256=:Wa9pbhd1Shuu4zoBfR85ivBCUE2v9NypwToClj2W2lU=:";
+const APP_JS_DIGEST: &str = "sha-256=Wa9pbhd1Shuu4zoBfR85ivBCUE2v9NypwToClj2W2lU=";
+
+fn digest_static() -> Static<hyper_staticfile::vfs::MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.digest_trailer(true);
+    static_
+}
+
+#[tokio::test]
+async fn trailer_carries_digests() {
+    let req = get("/app.js")
+        .header(header::TE, "trailers")
+        .body(())
+        .unwrap();
+    let res = serve(&digest_static(), req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::TRAILER), Some("content-digest, digest"));
+    assert_eq!(res.header(header::CONTENT_LENGTH), None);
+    assert_eq!(res.body, APP_JS);
+    let trailers = res.trailers.expect("missing trailers");
+    assert_eq!(trailers["content-digest"], APP_JS_CONTENT_DIGEST);
+    assert_eq!(trailers["digest"], APP_JS_DIGEST);
+}
+
+#[tokio::test]
+async fn no_trailer_without_te() {
+    let res = serve(&digest_static(), get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::TRAILER), None);
+    assert_eq!(
+        res.header(header::CONTENT_LENGTH),
+        Some(APP_JS.len().to_string().as_str())
+    );
+    assert_eq!(res.body, APP_JS);
+    assert!(res.trailers.is_none());
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{get, memory_fs, mtime, serve};
+use http::StatusCode;
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+fn static_() -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add(".env", Bytes::from_static(b"SECRET=1"), Some(mtime()));
+    fs.add(
+        "static/.git/HEAD",
+        Bytes::from_static(b"ref"),
+        Some(mtime()),
+    );
+    fs.add("foo.bar/baz", Bytes::from_static(b"baz"), Some(mtime()));
+    Static::from_memory_fs(fs)
+}
+
+#[tokio::test]
+async fn hidden_paths_are_not_found() {
+    let static_ = static_();
+    for path in ["/.env", "/static/.git/HEAD"] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND, "{path}");
+    }
+
+    // 不在开头的点不受影响
+    let res = serve(&static_, get("/foo.bar/baz").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, "baz");
+}
+
+#[tokio::test]
+async fn opt_out_serves_dotfiles() {
+    let mut static_ = static_();
+    static_.serve_dotfiles(true);
+    for path in ["/.env", "/static/.git/HEAD"] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::OK, "{path}");
+    }
+}
@@ -0,0 +1,482 @@
+mod common;
+
+use std::path::Path;
+
+use common::{get, memory_fs, mtime, serve, APP_JS, INDEX_HTML};
+use http::{header, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{
+    util::ServedFile, vfs::MemoryFs, AcceptEncoding, Encoding, ResolveResult, Static,
+};
+
+const WASM: &[u8] = b"\0asm\x01\0\0\0";
+const WASM_GZ: &[u8] = b"pretend this is gzip";
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+const PNG_GZ: &[u8] = b"pretend this is a gzipped png";
+
+fn static_() -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add("app.wasm", Bytes::from_static(WASM), Some(mtime()));
+    fs.add("app.wasm.gz", Bytes::from_static(WASM_GZ), Some(mtime()));
+    fs.add("logo.png", Bytes::from_static(PNG), Some(mtime()));
+    fs.add("logo.png.gz", Bytes::from_static(PNG_GZ), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.allowed_encodings(AcceptEncoding::all());
+    static_
+}
+
+fn gzip(path: &str) -> http::Request<()> {
+    get(path)
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .body(())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn sidecar_served_for_type_outside_compressible_set() {
+    let res = serve(&static_(), gzip("/app.wasm")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(res.header(header::CONTENT_TYPE), Some("application/wasm"));
+    assert_eq!(res.body, WASM_GZ);
+
+    let res = serve(&static_(), gzip("/logo.png")).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(res.body, PNG_GZ);
+}
+
+#[tokio::test]
+async fn identity_without_accept_encoding() {
+    let res = serve(&static_(), get("/app.wasm").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), None);
+    assert_eq!(res.body, WASM);
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn on_the_fly_only_for_compressible_types() {
+    let mut fs = memory_fs();
+    fs.add("app.wasm", Bytes::from_static(WASM), Some(mtime()));
+    fs.add("logo.png", Bytes::from_static(PNG), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_
+        .allowed_encodings(AcceptEncoding::all())
+        .compress_on_the_fly(AcceptEncoding::all());
+
+    let res = serve(&static_, gzip("/app.js")).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    let res = serve(&static_, gzip("/app.wasm")).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), None);
+    assert_eq!(res.body, WASM);
+
+    static_
+        .resolver
+        .compressible_types
+        .insert("application/wasm".parse().unwrap());
+    let res = serve(&static_, gzip("/app.wasm")).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    let res = serve(&static_, gzip("/logo.png")).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), None);
+    assert_eq!(res.body, PNG);
+}
+
+#[tokio::test]
+async fn sidecar_content_length_is_compressed_size() {
+    // 预压缩文件比原文件短，Content-Length不能是原文件的大小
+    const APP_JS_GZ: &[u8] = b"gzipped app.js";
+    let mut fs = memory_fs();
+    fs.add("app.js.gz", Bytes::from_static(APP_JS_GZ), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.allowed_encodings(AcceptEncoding::all());
+
+    let res = serve(&static_, gzip("/app.js")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(
+        res.header(header::CONTENT_LENGTH),
+        Some(APP_JS_GZ.len().to_string().as_str())
+    );
+    assert_ne!(APP_JS_GZ.len(), APP_JS.len());
+    assert_eq!(res.body.len(), APP_JS_GZ.len());
+    assert_eq!(res.body, APP_JS_GZ);
+
+    let req = http::Request::head("/app.js")
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(
+        res.header(header::CONTENT_LENGTH),
+        Some(APP_JS_GZ.len().to_string().as_str())
+    );
+}
+
+fn vary(res: &common::TestResponse) -> Vec<&str> {
+    res.parts
+        .headers
+        .get_all(header::VARY)
+        .iter()
+        .flat_map(|v| v.to_str().unwrap().split(','))
+        .map(str::trim)
+        .collect()
+}
+
+#[tokio::test]
+async fn vary_accept_encoding_on_compressed_and_identity() {
+    let static_ = static_();
+    let res = serve(&static_, gzip("/app.wasm")).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(vary(&res), ["accept-encoding"]);
+
+    let res = serve(&static_, get("/app.wasm").body(()).unwrap()).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), None);
+    assert_eq!(vary(&res), ["accept-encoding"]);
+
+    // 没有预压缩文件时同样需要
+    let res = serve(&static_, gzip("/data.bin")).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), None);
+    assert_eq!(vary(&res), ["accept-encoding"]);
+}
+
+#[tokio::test]
+async fn no_vary_without_allowed_encodings() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let res = serve(&static_, gzip("/app.js")).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), None);
+    assert!(vary(&res).is_empty());
+}
+
+#[tokio::test]
+async fn vary_not_duplicated_by_extra_headers() {
+    let mut static_ = static_();
+    static_.extra_header(
+        header::VARY,
+        http::HeaderValue::from_static("Accept-Encoding"),
+    );
+    for req in [gzip("/app.wasm"), get("/app.wasm").body(()).unwrap()] {
+        let res = serve(&static_, req).await;
+        assert_eq!(vary(&res), ["accept-encoding"]);
+    }
+}
+
+#[tokio::test]
+async fn br_sidecar_served_byte_for_byte() {
+    // 包含所有字节值，任何转换都会改变内容
+    let br: Bytes = (0..=255u8).rev().chain(0..=255).collect::<Vec<_>>().into();
+    let mut fs = memory_fs();
+    fs.add("app.js.br", br.clone(), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.allowed_encodings(AcceptEncoding::all());
+
+    let req = get("/app.js")
+        .header(header::ACCEPT_ENCODING, "br")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("br"));
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("text/javascript; charset=utf-8")
+    );
+    assert_eq!(res.body, br);
+
+    // 范围请求针对的是压缩后的字节
+    let req = get("/app.js")
+        .header(header::ACCEPT_ENCODING, "br")
+        .header(header::RANGE, "bytes=250-261")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("br"));
+    assert_eq!(res.header(header::CONTENT_RANGE), Some("bytes 250-261/512"));
+    assert_eq!(res.body, br.slice(250..262));
+}
+
+#[tokio::test]
+async fn sidecar_follows_client_preference() {
+    let mut fs = memory_fs();
+    fs.add("app.js.gz", Bytes::from_static(b"gz"), Some(mtime()));
+    fs.add("app.js.br", Bytes::from_static(b"br"), Some(mtime()));
+    fs.add("app.js.zst", Bytes::from_static(b"zst"), Some(mtime()));
+    fs.add("data.bin.gz", Bytes::from_static(b"gz"), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.allowed_encodings(AcceptEncoding::all());
+
+    for (path, accept, expected) in [
+        ("/app.js", "gzip;q=1.0, zstd;q=0.1", "gzip"),
+        ("/app.js", "zstd;q=0.5, br;q=0.9, gzip;q=0.1", "br"),
+        // 权重相同时按zstd、br、gzip的顺序
+        ("/app.js", "gzip, br, zstd", "zstd"),
+        ("/app.js", "gzip, br", "br"),
+        // 首选编码没有预压缩文件时使用下一个
+        ("/data.bin", "zstd, br;q=0.9, gzip;q=0.1", "gzip"),
+    ] {
+        let req = get(path)
+            .header(header::ACCEPT_ENCODING, accept)
+            .body(())
+            .unwrap();
+        let res = serve(&static_, req).await;
+        assert_eq!(res.status(), StatusCode::OK, "{accept}");
+        assert_eq!(
+            res.header(header::CONTENT_ENCODING),
+            Some(expected),
+            "{accept}"
+        );
+    }
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn on_the_fly_gzip_round_trip() {
+    use std::io::Read;
+
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_
+        .allowed_encodings(AcceptEncoding::all())
+        .compress_on_the_fly(AcceptEncoding::all());
+
+    let res = serve(&static_, gzip("/app.js")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    // 压缩后的长度未知
+    assert_eq!(res.header(header::CONTENT_LENGTH), None);
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(&res.body[..])
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, APP_JS);
+}
+
+#[cfg(feature = "zstd")]
+#[tokio::test]
+async fn on_the_fly_zstd_round_trip() {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_
+        .allowed_encodings(AcceptEncoding::all())
+        .compress_on_the_fly(AcceptEncoding::all());
+
+    let req = http::Request::get("/app.js")
+        .header(header::ACCEPT_ENCODING, "zstd")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("zstd"));
+    assert_eq!(res.header(header::CONTENT_LENGTH), None);
+    let decoded = zstd::stream::decode_all(&res.body[..]).unwrap();
+    assert_eq!(decoded, APP_JS);
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn on_the_fly_gzip_ignores_range() {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_
+        .allowed_encodings(AcceptEncoding::all())
+        .compress_on_the_fly(AcceptEncoding::all());
+
+    let req = http::Request::get("/app.js")
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .header(header::RANGE, "bytes=0-3")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(res.header(header::ACCEPT_RANGES), Some("none"));
+    assert_eq!(res.header(header::CONTENT_RANGE), None);
+}
+
+#[tokio::test]
+async fn zstd_sidecar_resolved() {
+    const INDEX_ZST: &[u8] = b"pretend this is zstd";
+    let mut fs = memory_fs();
+    fs.add(
+        "index.html.zst",
+        Bytes::from_static(INDEX_ZST),
+        Some(mtime()),
+    );
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.allowed_encodings(AcceptEncoding::all());
+
+    let req = get("/index.html")
+        .header(header::ACCEPT_ENCODING, "zstd")
+        .body(())
+        .unwrap();
+    let ResolveResult::Found(file) = static_.resolver.resolve_request(&req).await.unwrap() else {
+        panic!("index.html not found");
+    };
+    assert_eq!(file.encoding, Some(Encoding::Zstd));
+    assert_eq!(file.path, Path::new("index.html.zst"));
+    assert_eq!(file.size, INDEX_ZST.len() as u64);
+
+    let res = serve(&static_, req).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("zstd"));
+    assert_eq!(res.body, INDEX_ZST);
+}
+
+#[tokio::test]
+async fn deflate_sidecar_resolved() {
+    const APP_JS_ZZ: &[u8] = b"pretend this is zlib";
+    let mut fs = memory_fs();
+    fs.add("app.js.zz", Bytes::from_static(APP_JS_ZZ), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.allowed_encodings(AcceptEncoding::all());
+
+    let req = get("/app.js")
+        .header(header::ACCEPT_ENCODING, "deflate")
+        .body(())
+        .unwrap();
+    let ResolveResult::Found(file) = static_.resolver.resolve_request(&req).await.unwrap() else {
+        panic!("app.js not found");
+    };
+    assert_eq!(file.encoding, Some(Encoding::Deflate));
+    assert_eq!(file.path, Path::new("app.js.zz"));
+
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("deflate"));
+    assert_eq!(
+        res.header(header::CONTENT_LENGTH),
+        Some(APP_JS_ZZ.len().to_string().as_str())
+    );
+    assert_eq!(res.body, APP_JS_ZZ);
+
+    // 不接受deflate时返回原文件
+    let res = serve(&static_, gzip("/app.js")).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), None);
+    assert_eq!(res.body, APP_JS);
+}
+
+#[tokio::test]
+async fn sidecar_from_separate_root() {
+    const APP_JS_GZ: &[u8] = b"gzip from the sidecar root";
+    let mut sidecars = MemoryFs::default();
+    sidecars.add("app.js.gz", Bytes::from_static(APP_JS_GZ), Some(mtime()));
+    // 主目录中的预压缩文件不再使用
+    let mut fs = memory_fs();
+    fs.add("app.js.gz", Bytes::from_static(b"stale"), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.allowed_encodings(AcceptEncoding::all());
+    static_
+        .resolver
+        .set_sidecar_opener(Encoding::Gzip, sidecars);
+
+    let res = serve(&static_, gzip("/app.js")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(res.body, APP_JS_GZ);
+
+    // 预压缩目录中没有对应文件时返回原文件
+    let res = serve(&static_, gzip("/index.html")).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), None);
+}
+
+#[tokio::test]
+async fn forced_gzip_without_accept_encoding() {
+    const APP_JS_GZ: &[u8] = b"gzipped app.js";
+    let mut fs = memory_fs();
+    fs.add("app.js.gz", Bytes::from_static(APP_JS_GZ), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.force_encoding(Some(Encoding::Gzip));
+
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(res.body, APP_JS_GZ);
+
+    // 没有预压缩文件时返回原文件
+    let res = serve(&static_, get("/index.html").body(()).unwrap()).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), None);
+    assert_eq!(res.body, INDEX_HTML);
+}
+
+#[tokio::test]
+async fn logical_path_is_the_requested_file() {
+    let mut fs = memory_fs();
+    fs.add("app.js.br", Bytes::from_static(b"br"), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.allowed_encodings(AcceptEncoding::all());
+
+    let req = get("/app.js")
+        .header(header::ACCEPT_ENCODING, "br")
+        .body(())
+        .unwrap();
+    let ResolveResult::Found(file) = static_.resolver.resolve_request(&req).await.unwrap() else {
+        panic!("app.js not found");
+    };
+    assert_eq!(file.encoding, Some(Encoding::Br));
+    assert_eq!(file.logical_path, Path::new("app.js"));
+    assert_eq!(file.path, Path::new("app.js.br"));
+
+    // 没有协商编码时两者相同
+    let req = get("/app.js").body(()).unwrap();
+    let ResolveResult::Found(file) = static_.resolver.resolve_request(&req).await.unwrap() else {
+        panic!("app.js not found");
+    };
+    assert_eq!(file.logical_path, Path::new("app.js"));
+    assert_eq!(file.path, Path::new("app.js"));
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn precompressed_gzip_is_served() {
+    use std::io::Read;
+
+    use hyper_staticfile::vfs::Precompress;
+
+    let page = "<p>hello</p>\n".repeat(100);
+    let mut fs = memory_fs();
+    fs.add("page.html", Bytes::from(page.clone()), Some(mtime()));
+    fs.precompress(&Precompress::new(vec![Encoding::Gzip]))
+        .unwrap();
+    assert!(fs.contains("page.html.gz"));
+    // 太小压缩后没有变小的文件不生成
+    assert!(!fs.contains("index.html.gz"));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.allowed_encodings(AcceptEncoding::all());
+
+    let res = serve(&static_, gzip("/page.html")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert!(res.body.len() < page.len());
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(&res.body[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, page);
+}
+
+#[tokio::test]
+async fn served_file_extension_reports_sidecar() {
+    let mut with_extension = static_();
+    with_extension.served_file_extension(true);
+
+    let res = serve(&with_extension, gzip("/app.wasm")).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(
+        res.parts.extensions.get::<ServedFile>(),
+        Some(&ServedFile {
+            path: "app.wasm.gz".into(),
+            size: WASM_GZ.len() as u64,
+            encoding: Some(Encoding::Gzip),
+        })
+    );
+
+    let res = serve(&with_extension, get("/app.wasm").body(()).unwrap()).await;
+    assert_eq!(
+        res.parts.extensions.get::<ServedFile>(),
+        Some(&ServedFile {
+            path: "app.wasm".into(),
+            size: WASM.len() as u64,
+            encoding: None,
+        })
+    );
+
+    // 默认不放入
+    let res = serve(&static_(), gzip("/app.wasm")).await;
+    assert_eq!(res.parts.extensions.get::<ServedFile>(), None);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::{get, memory_fs, serve};
+use http::{header, Request, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+const NOT_FOUND_PAGE: &[u8] = b"<h1>Not Found</h1>";
+
+fn static_() -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.error_page(
+        StatusCode::NOT_FOUND,
+        Bytes::from_static(NOT_FOUND_PAGE),
+        "text/html; charset=utf-8",
+    );
+    static_
+}
+
+#[tokio::test]
+async fn custom_not_found_page() {
+    let res = serve(&static_(), get("/missing.html").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("text/html; charset=utf-8")
+    );
+    assert_eq!(
+        res.header(header::CONTENT_LENGTH),
+        Some(NOT_FOUND_PAGE.len().to_string().as_str())
+    );
+    assert_eq!(res.body, NOT_FOUND_PAGE);
+
+    let res = serve(&static_(), Request::head("/missing.html").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        res.header(header::CONTENT_LENGTH),
+        Some(NOT_FOUND_PAGE.len().to_string().as_str())
+    );
+    assert!(res.body.is_empty());
+}
+
+#[tokio::test]
+async fn other_errors_keep_empty_body() {
+    let req = Request::post("/index.html").body(()).unwrap();
+    let res = serve(&static_(), req).await;
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(res.header(header::CONTENT_TYPE), None);
+    assert!(res.body.is_empty());
+
+    let default = Static::from_memory_fs(memory_fs());
+    let res = serve(&default, get("/missing.html").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert!(res.body.is_empty());
+}
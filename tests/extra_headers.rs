@@ -0,0 +1,65 @@
+mod common;
+
+use common::{get, memory_fs, serve};
+use http::{header, HeaderValue, StatusCode};
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+fn static_() -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_
+        .extra_header(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        )
+        .extra_header(
+            header::STRICT_TRANSPORT_SECURITY,
+            HeaderValue::from_static("max-age=63072000"),
+        );
+    static_
+}
+
+#[tokio::test]
+async fn extra_headers_on_every_status() {
+    let static_ = static_();
+    for (req, status) in [
+        (get("/app.js").body(()).unwrap(), StatusCode::OK),
+        (get("/missing.js").body(()).unwrap(), StatusCode::NOT_FOUND),
+        (
+            get("/data.bin")
+                .header(header::RANGE, "bytes=0-9")
+                .body(())
+                .unwrap(),
+            StatusCode::PARTIAL_CONTENT,
+        ),
+    ] {
+        let res = serve(&static_, req).await;
+        assert_eq!(res.status(), status);
+        assert_eq!(
+            res.header(header::X_CONTENT_TYPE_OPTIONS),
+            Some("nosniff"),
+            "{status}"
+        );
+        assert_eq!(
+            res.header(header::STRICT_TRANSPORT_SECURITY),
+            Some("max-age=63072000"),
+            "{status}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn override_replaces_existing_headers() {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.extra_header(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+
+    // 默认保留响应中已有的同名头部
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("text/javascript; charset=utf-8")
+    );
+
+    static_.override_headers(true);
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.header(header::CONTENT_TYPE), Some("text/plain"));
+}
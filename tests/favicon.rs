@@ -0,0 +1,47 @@
+mod common;
+
+use common::{get, memory_fs, mtime, serve};
+use http::{header, Request, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+const DEFAULT_ICON: &[u8] = b"\0\0\x01\0default icon";
+
+fn static_(fs: MemoryFs) -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.default_favicon(Bytes::from_static(DEFAULT_ICON), "image/x-icon");
+    static_
+}
+
+#[tokio::test]
+async fn default_favicon_when_missing() {
+    let static_ = static_(memory_fs());
+    let res = serve(&static_, get("/favicon.ico").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_TYPE), Some("image/x-icon"));
+    assert_eq!(res.body, DEFAULT_ICON);
+
+    let res = serve(&static_, Request::head("/favicon.ico").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.header(header::CONTENT_LENGTH),
+        Some(DEFAULT_ICON.len().to_string().as_str())
+    );
+
+    // 其它不存在的文件仍然是404
+    let res = serve(&static_, get("/missing.ico").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn existing_favicon_wins() {
+    let mut fs = memory_fs();
+    fs.add(
+        "favicon.ico",
+        Bytes::from_static(b"real icon"),
+        Some(mtime()),
+    );
+    let res = serve(&static_(fs), get("/favicon.ico").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, &b"real icon"[..]);
+}
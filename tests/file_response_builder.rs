@@ -0,0 +1,123 @@
+mod common;
+
+use std::io::{Cursor, Error as IoError};
+
+use common::mtime;
+use http::{header, response::Parts, Request, StatusCode};
+use http_body_util::BodyExt;
+use hyper::body::Bytes;
+use hyper_staticfile::{
+    util::{FileResponseBuilder, RangeOrder},
+    Body, ResolvedFile,
+};
+
+const GENERATED: &[u8] = b"generated at runtime";
+
+fn generated() -> ResolvedFile<Cursor<Bytes>> {
+    ResolvedFile::from_parts(
+        Cursor::new(Bytes::from_static(GENERATED)),
+        "generated.txt",
+        GENERATED.len() as u64,
+        Some(mtime()),
+        Some("text/plain".to_string()),
+        None,
+    )
+}
+
+async fn build(req: Request<()>, file: ResolvedFile<Cursor<Bytes>>) -> (Parts, Bytes) {
+    let res = FileResponseBuilder::new().request(req).build(file).unwrap();
+    let (parts, body) = res.into_parts();
+    (parts, body.collect().await.unwrap().to_bytes())
+}
+
+#[tokio::test]
+async fn from_parts_serves_full_body() {
+    let (parts, body) = build(Request::get("/").body(()).unwrap(), generated()).await;
+    assert_eq!(parts.status, StatusCode::OK);
+    assert_eq!(parts.headers[header::CONTENT_TYPE], "text/plain");
+    assert_eq!(
+        parts.headers[header::CONTENT_LENGTH],
+        GENERATED.len().to_string()
+    );
+    assert!(parts.headers.contains_key(header::ETAG));
+    assert!(parts.headers.contains_key(header::LAST_MODIFIED));
+    assert_eq!(body, GENERATED);
+}
+
+#[tokio::test]
+async fn from_parts_serves_range() {
+    let req = Request::get("/")
+        .header(header::RANGE, "bytes=0-8")
+        .body(())
+        .unwrap();
+    let (parts, body) = build(req, generated()).await;
+    assert_eq!(parts.status, StatusCode::PARTIAL_CONTENT);
+    assert_eq!(parts.headers[header::CONTENT_RANGE], "bytes 0-8/20");
+    assert_eq!(body, &GENERATED[..9]);
+}
+
+/// 多段响应中各段Content-Range出现的顺序
+fn part_ranges(body: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(body)
+        .lines()
+        .filter_map(|line| line.strip_prefix("Content-Range: "))
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test]
+async fn multipart_range_order() {
+    for (order, expected) in [
+        (RangeOrder::Request, ["bytes 10-14/20", "bytes 0-4/20"]),
+        (RangeOrder::Sorted, ["bytes 0-4/20", "bytes 10-14/20"]),
+    ] {
+        let req = Request::get("/")
+            .header(header::RANGE, "bytes=10-14,0-4")
+            .body(())
+            .unwrap();
+        let res = FileResponseBuilder::new()
+            .request(req)
+            .range_order(order)
+            .build(generated())
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(part_ranges(&body), expected, "{order:?}");
+    }
+}
+
+#[tokio::test]
+async fn stream_body_ignores_range() {
+    let req = Request::get("/")
+        .header(header::RANGE, "bytes=0-3")
+        .body(())
+        .unwrap();
+    let stream = futures_util::stream::iter([Ok::<_, IoError>(Bytes::from_static(GENERATED))]);
+    let res: http::Response<Body<Cursor<Bytes>>> = FileResponseBuilder::new()
+        .request(req)
+        .build_stream(stream, Some(mtime()), Some("text/plain".to_string()))
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::ACCEPT_RANGES], "none");
+    assert!(!res.headers().contains_key(header::CONTENT_RANGE));
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, GENERATED);
+}
+
+#[tokio::test]
+async fn stream_body_yields_each_chunk() {
+    let chunks =
+        ["first ", "second ", "third"].map(|chunk| Ok(Bytes::from_static(chunk.as_bytes())));
+    let res: http::Response<Body<Cursor<Bytes>>> = FileResponseBuilder::new()
+        .build_stream(futures_util::stream::iter(chunks), None, None)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(!res.headers().contains_key(header::CONTENT_LENGTH));
+
+    let mut body = res.into_body();
+    let mut frames = Vec::new();
+    while let Some(frame) = body.frame().await {
+        frames.push(frame.unwrap().into_data().unwrap());
+    }
+    assert_eq!(frames, ["first ", "second ", "third"]);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use std::path::Path;
+
+use common::TempDir;
+use hyper_staticfile::vfs::{FromDirOptions, MemoryFs};
+
+fn site() -> TempDir {
+    let dir = TempDir::new("from-dir");
+    dir.write("index.html", b"<h1>index</h1>");
+    dir.write("assets/app.js", b"console.log(1);");
+    dir.write("assets/app.js.map", b"{}");
+    dir.write("node_modules/pkg/index.js", b"module.exports = 1;");
+    dir
+}
+
+fn sorted_paths(fs: &MemoryFs) -> Vec<&Path> {
+    let mut paths: Vec<_> = fs.paths().collect();
+    paths.sort();
+    paths
+}
+
+#[tokio::test]
+async fn filter_skips_files_and_directories() {
+    let dir = site();
+    let fs = MemoryFs::from_dir_with(
+        dir.path(),
+        FromDirOptions::new().filter(|path| {
+            path != Path::new("node_modules") && path.extension().is_none_or(|ext| ext != "map")
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        sorted_paths(&fs),
+        [Path::new("assets/app.js"), Path::new("index.html")]
+    );
+    assert_eq!(fs.get("assets/app.js").unwrap().handle, "console.log(1);");
+
+    let all = MemoryFs::from_dir(dir.path()).await.unwrap();
+    assert_eq!(sorted_paths(&all).len(), 4);
+}
+
+#[tokio::test]
+async fn byte_limit_is_enforced() {
+    let dir = site();
+    // 四个文件共50字节
+    let total = 14 + 15 + 2 + 19;
+    let fs = MemoryFs::from_dir_with(
+        dir.path(),
+        FromDirOptions::new().max_total_bytes(Some(total)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(sorted_paths(&fs).len(), 4);
+
+    let err = MemoryFs::from_dir_with(
+        dir.path(),
+        FromDirOptions::new().max_total_bytes(Some(total - 1)),
+    )
+    .await
+    .err()
+    .unwrap();
+    assert!(err.to_string().contains("exceeds the limit"), "{err}");
+}
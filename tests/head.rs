@@ -0,0 +1,97 @@
+mod common;
+
+use common::{memory_fs, mtime, serve, TestResponse};
+use http::{header, Method, Request, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, AcceptEncoding, Static};
+
+fn static_() -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add(
+        "app.js.gz",
+        Bytes::from_static(b"gzipped app.js"),
+        Some(mtime()),
+    );
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.allowed_encodings(AcceptEncoding::all());
+    static_
+}
+
+async fn send(
+    static_: &Static<MemoryFs>,
+    method: Method,
+    path: &str,
+    headers: &[(header::HeaderName, &str)],
+) -> TestResponse {
+    let mut req = Request::builder().method(method).uri(path);
+    for (name, value) in headers {
+        req = req.header(name, *value);
+    }
+    serve(static_, req.body(()).unwrap()).await
+}
+
+/// HEAD与GET的状态码和头部完全相同，只是没有响应体
+async fn assert_head_matches_get(
+    static_: &Static<MemoryFs>,
+    path: &str,
+    headers: &[(header::HeaderName, &str)],
+) -> TestResponse {
+    let get = send(static_, Method::GET, path, headers).await;
+    let head = send(static_, Method::HEAD, path, headers).await;
+    assert_eq!(head.status(), get.status(), "{path}");
+    assert_eq!(head.parts.headers, get.parts.headers, "{path}");
+    assert!(head.body.is_empty(), "{path}");
+    get
+}
+
+#[tokio::test]
+async fn head_matches_get_for_identity() {
+    let static_ = static_();
+    for path in ["/app.js", "/data.bin", "/docs/"] {
+        let res = assert_head_matches_get(&static_, path, &[]).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.header(header::CONTENT_ENCODING), None);
+    }
+}
+
+#[tokio::test]
+async fn head_matches_get_for_gzip() {
+    let static_ = static_();
+    let res =
+        assert_head_matches_get(&static_, "/app.js", &[(header::ACCEPT_ENCODING, "gzip")]).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(res.header(header::CONTENT_LENGTH), Some("14"));
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn head_matches_get_for_on_the_fly_gzip() {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_
+        .allowed_encodings(AcceptEncoding::all())
+        .compress_on_the_fly(AcceptEncoding::all());
+    let res =
+        assert_head_matches_get(&static_, "/app.js", &[(header::ACCEPT_ENCODING, "gzip")]).await;
+    assert_eq!(res.header(header::CONTENT_ENCODING), Some("gzip"));
+}
+
+#[tokio::test]
+async fn head_with_range() {
+    let static_ = static_();
+    let res =
+        assert_head_matches_get(&static_, "/data.bin", &[(header::RANGE, "bytes=10-19")]).await;
+    assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(res.header(header::CONTENT_RANGE), Some("bytes 10-19/1024"));
+    assert_eq!(res.header(header::CONTENT_LENGTH), Some("10"));
+
+    let res =
+        assert_head_matches_get(&static_, "/data.bin", &[(header::RANGE, "bytes=2000-")]).await;
+    assert_eq!(res.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(res.header(header::CONTENT_RANGE), Some("bytes */1024"));
+
+    // 格式错误的Range被忽略
+    let res = assert_head_matches_get(&static_, "/data.bin", &[(header::RANGE, "bytes=abc")]).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_RANGE), None);
+    assert_eq!(res.header(header::CONTENT_LENGTH), Some("1024"));
+}
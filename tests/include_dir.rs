@@ -0,0 +1,109 @@
+#![cfg(feature = "include_dir")]
+
+mod common;
+
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::Duration;
+
+use common::{get, serve};
+use http::{header, StatusCode};
+use hyper_staticfile::{
+    vfs::{FileOpener, IncludeDirOpener, MemoryFs},
+    Static,
+};
+use include_dir::{include_dir, Dir};
+
+static SITE: Dir = include_dir!("$CARGO_MANIFEST_DIR/tests/fixtures/site");
+
+async fn etag(static_: &Static<MemoryFs>) -> String {
+    let res = serve(static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, SITE.get_file("app.js").unwrap().contents());
+    res.header(header::ETAG).unwrap().to_string()
+}
+
+/// 模拟两次启动进程，各自从嵌入的文件构建`MemoryFs`
+async fn etags_of_two_starts(configure: fn(&mut Static<MemoryFs>)) -> (String, String) {
+    let mut first = Static::from_memory_fs(MemoryFs::from_include_dir(&SITE).unwrap());
+    configure(&mut first);
+    // 没有嵌入修改时间时使用构建时的时间，两次构建的时间必然不同
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let mut second = Static::from_memory_fs(MemoryFs::from_include_dir(&SITE).unwrap());
+    configure(&mut second);
+    (etag(&first).await, etag(&second).await)
+}
+
+#[cfg(feature = "include-dir-metadata")]
+#[tokio::test]
+async fn embedded_mtime_keeps_etag_stable() {
+    let (first, second) = etags_of_two_starts(|_| {}).await;
+    assert_eq!(first, second);
+}
+
+#[cfg(not(feature = "include-dir-metadata"))]
+#[tokio::test]
+async fn etag_without_embedded_mtime_uses_build_time() {
+    let (first, second) = etags_of_two_starts(|_| {}).await;
+    assert_ne!(first, second);
+}
+
+#[cfg(feature = "digest")]
+#[tokio::test]
+async fn strong_etag_is_stable_for_same_bytes() {
+    let (first, second) = etags_of_two_starts(|static_| {
+        static_.strong_etag(true);
+    })
+    .await;
+    assert!(!first.starts_with("W/"));
+    assert_eq!(first, second);
+}
+
+#[tokio::test]
+async fn opener_serves_embedded_files() {
+    let static_ = Static::with_opener(IncludeDirOpener::new(&SITE));
+    for (path, file) in [
+        ("/", "index.html"),
+        ("/app.js", "app.js"),
+        ("/docs/readme.txt", "docs/readme.txt"),
+    ] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::OK, "{path}");
+        assert_eq!(res.body, SITE.get_file(file).unwrap().contents(), "{path}");
+    }
+
+    let res = serve(&static_, get("/docs").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.header(header::LOCATION), Some("/docs/"));
+    let res = serve(&static_, get("/missing.txt").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn opener_reports_directories() {
+    let opener = IncludeDirOpener::new(&SITE);
+    let docs = opener.open(Path::new("docs")).await.unwrap();
+    assert!(docs.is_dir);
+    let readme = opener.open(Path::new("docs/readme.txt")).await.unwrap();
+    assert!(!readme.is_dir);
+    assert_eq!(readme.size, b"embedded docs\n".len() as u64);
+    let err = opener.open(Path::new("docs/missing")).await.err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    let mut names: Vec<_> = opener
+        .read_dir(Path::new(""))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| (entry.name, entry.is_dir))
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            ("app.js".to_string(), false),
+            ("docs".to_string(), true),
+            ("index.html".to_string(), false),
+        ]
+    );
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::{get, memory_fs, mtime, serve, INDEX_HTML};
+use http::{header, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+fn static_() -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add("htm/index.htm", Bytes::from_static(b"htm"), Some(mtime()));
+    fs.add("fr/index.fr.html", Bytes::from_static(b"fr"), Some(mtime()));
+    fs.add("fr/index.htm", Bytes::from_static(b"htm"), Some(mtime()));
+    // index.html是目录，跳过
+    fs.add("dir/index.fr.html/x", Bytes::new(), Some(mtime()));
+    fs.add("dir/index.htm", Bytes::from_static(b"htm"), Some(mtime()));
+    fs.add("empty/readme.txt", Bytes::new(), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.index_files(vec![
+        "index.fr.html".to_string(),
+        "index.html".to_string(),
+        "index.htm".to_string(),
+    ]);
+    static_
+}
+
+#[tokio::test]
+async fn first_existing_candidate_is_served() {
+    let static_ = static_();
+    for (path, expected) in [
+        ("/htm/", &b"htm"[..]),
+        ("/fr/", b"fr"),
+        ("/dir/", b"htm"),
+        ("/docs/", INDEX_HTML),
+    ] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::OK, "{path}");
+        assert_eq!(res.body, expected, "{path}");
+    }
+}
+
+#[tokio::test]
+async fn no_candidate_is_not_found() {
+    let res = serve(&static_(), get("/empty/").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn directory_still_redirects_to_trailing_slash() {
+    let res = serve(&static_(), get("/htm").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.header(header::LOCATION), Some("/htm/"));
+}
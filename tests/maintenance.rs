@@ -0,0 +1,46 @@
+mod common;
+
+use common::{get, memory_fs, serve, APP_JS};
+use http::{header, Request, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+use mime_guess::mime;
+
+const PAGE: &[u8] = b"<h1>down for maintenance</h1>";
+
+fn static_() -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.maintenance(Some((
+        StatusCode::SERVICE_UNAVAILABLE,
+        Bytes::from_static(PAGE),
+        mime::TEXT_HTML_UTF_8,
+    )));
+    static_
+}
+
+#[tokio::test]
+async fn existing_file_gets_maintenance_page() {
+    for path in ["/app.js", "/index.html", "/missing.html"] {
+        let res = serve(&static_(), get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE, "{path}");
+        assert_eq!(
+            res.header(header::CONTENT_TYPE),
+            Some("text/html; charset=utf-8"),
+            "{path}"
+        );
+        assert_eq!(res.body, PAGE, "{path}");
+    }
+
+    let res = serve(&static_(), Request::head("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(res.body.is_empty());
+}
+
+#[tokio::test]
+async fn disabling_restores_files() {
+    let mut static_ = static_();
+    static_.maintenance(None);
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+}
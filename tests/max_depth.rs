@@ -0,0 +1,37 @@
+mod common;
+
+use common::{get, memory_fs, mtime, serve};
+use http::StatusCode;
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+fn static_() -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add("a/b/c", Bytes::from_static(b"c"), Some(mtime()));
+    fs.add("a/b/x/d", Bytes::from_static(b"d"), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.max_depth(Some(3));
+    static_
+}
+
+#[tokio::test]
+async fn deeper_paths_are_not_found() {
+    let static_ = static_();
+    let res = serve(&static_, get("/a/b/c").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, "c");
+
+    // 文件存在也返回404
+    for path in ["/a/b/c/d", "/a/b/x/d"] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND, "{path}");
+    }
+}
+
+#[tokio::test]
+async fn no_limit_when_unset() {
+    let mut static_ = static_();
+    static_.max_depth(None);
+    let res = serve(&static_, get("/a/b/x/d").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
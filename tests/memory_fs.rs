@@ -0,0 +1,74 @@
+mod common;
+
+use std::path::Path;
+
+use common::{get, memory_fs, mtime, serve, APP_JS};
+use http::{header, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+const FOO: &[u8] = b"foo is a file";
+
+#[tokio::test]
+async fn file_wins_over_directory_placeholder() {
+    // 先后顺序不同：文件覆盖已有的文件夹占位，或文件夹占位不覆盖已有的文件
+    let mut dir_first = MemoryFs::default();
+    dir_first.add("foo/bar", Bytes::from_static(b"bar"), Some(mtime()));
+    dir_first.add("foo", Bytes::from_static(FOO), Some(mtime()));
+    let mut file_first = MemoryFs::default();
+    file_first.add("foo", Bytes::from_static(FOO), Some(mtime()));
+    file_first.add("foo/bar", Bytes::from_static(b"bar"), Some(mtime()));
+
+    for fs in [dir_first, file_first] {
+        assert!(fs.contains("foo"));
+        let static_ = Static::from_memory_fs(fs);
+        let res = serve(&static_, get("/foo").body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.header(header::LOCATION), None);
+        assert_eq!(res.body, FOO);
+    }
+}
+
+#[test]
+fn get_contains_and_paths() {
+    let fs = memory_fs();
+    let app = fs.get("app.js").unwrap();
+    assert_eq!(app.handle, APP_JS);
+    assert_eq!(app.size, APP_JS.len() as u64);
+    assert_eq!(app.modified, Some(mtime()));
+
+    // 文件夹不算作文件
+    assert!(fs.contains("docs/index.html"));
+    assert!(!fs.contains("docs"));
+    assert!(fs.get("docs").is_none());
+    assert!(!fs.contains("missing.js"));
+
+    let mut paths: Vec<_> = fs.paths().collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        [
+            Path::new("app.js"),
+            Path::new("data.bin"),
+            Path::new("docs/index.html"),
+            Path::new("index.html"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn removed_file_is_not_served() {
+    let mut fs = memory_fs();
+    assert!(fs.remove("docs").is_none());
+    assert!(fs.contains("docs/index.html"));
+    let removed = fs.remove("app.js").unwrap();
+    assert_eq!(removed.handle, APP_JS);
+    assert!(fs.remove("app.js").is_none());
+    assert!(!fs.contains("app.js"));
+
+    let static_ = Static::from_memory_fs(fs);
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let res = serve(&static_, get("/docs/").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
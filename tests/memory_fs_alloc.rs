@@ -0,0 +1,83 @@
+//! 统计`MemoryFs::open`的内存分配次数，使用单独的测试程序以便替换全局分配器
+mod common;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+use common::memory_fs;
+use futures_util::FutureExt;
+use hyper_staticfile::vfs::FileOpener;
+
+/// 按线程统计分配次数，不受并行运行的其它测试影响
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// `f`执行期间当前线程的分配次数
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn counter_sees_allocations() {
+    // 带消息的错误需要分配，确认计数有效
+    let count = allocations(|| drop(Error::new(ErrorKind::NotFound, "file not found")));
+    assert!(count > 0);
+}
+
+#[test]
+fn miss_does_not_allocate() {
+    let fs = memory_fs();
+    let path = Path::new("missing.js");
+    let count = allocations(|| {
+        for _ in 0..1000 {
+            let err = fs.open(path).now_or_never().unwrap().unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::NotFound);
+        }
+    });
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn hit_does_not_allocate_per_open() {
+    let fs = memory_fs();
+    let path = Path::new("app.js");
+    let count = allocations(|| {
+        for _ in 0..1000 {
+            let file = fs.open(path).now_or_never().unwrap().unwrap();
+            assert!(!file.is_dir);
+        }
+    });
+    assert_eq!(count, 0);
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::{get, memory_fs, serve, APP_JS};
+use http::{header, Method, Request, StatusCode};
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+fn request(method: Method, path: &str) -> Request<()> {
+    Request::builder()
+        .method(method)
+        .uri(path)
+        .body(())
+        .unwrap()
+}
+
+fn static_(methods: Vec<Method>) -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.allowed_methods(methods);
+    static_
+}
+
+#[tokio::test]
+async fn setter_ignores_unservable_methods() {
+    let static_ = static_(vec![Method::GET, Method::POST, Method::HEAD, Method::GET]);
+    assert_eq!(
+        static_.resolver.allowed_methods,
+        [Method::GET, Method::HEAD]
+    );
+
+    for method in [Method::POST, Method::OPTIONS] {
+        let res = serve(&static_, request(method.clone(), "/app.js")).await;
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED, "{method}");
+        assert_eq!(res.header(header::ALLOW), Some("GET, HEAD"), "{method}");
+    }
+
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+}
+
+#[tokio::test]
+async fn allow_matches_the_method_check() {
+    // 直接修改字段时，Allow头同样不列出无法处理的方法
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.resolver.allowed_methods = vec![Method::GET, Method::PUT];
+
+    let res = serve(&static_, request(Method::PUT, "/app.js")).await;
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(res.header(header::ALLOW), Some("GET"));
+
+    let res = serve(&static_, request(Method::HEAD, "/app.js")).await;
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+    // 没有允许OPTIONS时同样返回405
+    let res = serve(&static_, request(Method::OPTIONS, "/app.js")).await;
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(res.header(header::ALLOW), Some("GET"));
+}
+
+#[tokio::test]
+async fn get_only() {
+    let static_ = static_(vec![Method::GET]);
+    let res = serve(&static_, request(Method::HEAD, "/app.js")).await;
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(res.header(header::ALLOW), Some("GET"));
+}
+
+#[tokio::test]
+async fn allow_lists_configured_methods() {
+    let static_ = static_(vec![Method::GET, Method::HEAD, Method::OPTIONS]);
+    let res = serve(&static_, request(Method::POST, "/app.js")).await;
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(res.header(header::ALLOW), Some("GET, HEAD, OPTIONS"));
+}
+
+#[tokio::test]
+async fn not_implemented_status() {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.method_not_implemented(true);
+    for method in [Method::PUT, Method::DELETE] {
+        let res = serve(&static_, request(method.clone(), "/app.js")).await;
+        assert_eq!(res.status(), StatusCode::NOT_IMPLEMENTED, "{method}");
+    }
+
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+}
+
+#[tokio::test]
+async fn post_is_method_not_allowed() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    for path in ["/app.js", "/missing.js"] {
+        let res = serve(&static_, request(Method::POST, path)).await;
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED, "{path}");
+        assert_eq!(
+            res.header(header::ALLOW),
+            Some("GET, HEAD, OPTIONS"),
+            "{path}"
+        );
+        assert!(res.body.is_empty(), "{path}");
+    }
+}
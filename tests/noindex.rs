@@ -0,0 +1,49 @@
+mod common;
+
+use common::{get, memory_fs, mtime, serve, INDEX_HTML};
+use http::StatusCode;
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+fn static_() -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add(
+        "hidden/index.html",
+        Bytes::from_static(INDEX_HTML),
+        Some(mtime()),
+    );
+    fs.add("hidden/.noindex", Bytes::new(), Some(mtime()));
+    fs.add(
+        "hidden/page.html",
+        Bytes::from_static(INDEX_HTML),
+        Some(mtime()),
+    );
+    let mut static_ = Static::from_memory_fs(fs);
+    static_
+        .noindex_marker(Some(".noindex".to_string()))
+        .autoindex(true);
+    static_
+}
+
+#[tokio::test]
+async fn marker_suppresses_index() {
+    let static_ = static_();
+    let res = serve(&static_, get("/hidden/").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // 目录中的其它文件和没有标记的目录不受影响
+    for path in ["/hidden/page.html", "/docs/"] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::OK, "{path}");
+        assert_eq!(res.body, INDEX_HTML, "{path}");
+    }
+}
+
+#[tokio::test]
+async fn index_served_without_option() {
+    let mut static_ = static_();
+    static_.noindex_marker(None);
+    let res = serve(&static_, get("/hidden/").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, INDEX_HTML);
+}
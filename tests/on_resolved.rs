@@ -0,0 +1,73 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::{get, memory_fs, serve, APP_JS};
+use http::{header, StatusCode};
+use hyper_staticfile::{vfs::MemoryFs, Priority, ResolveResult, Resolver, Static, PRIORITY};
+use mime_guess::mime;
+
+/// 定义中不需要`O: FileOpener`约束
+#[allow(dead_code)]
+struct Wrapper<O> {
+    resolver: Resolver<O>,
+    static_: Static<O>,
+}
+
+impl<O> Clone for Wrapper<O> {
+    fn clone(&self) -> Self {
+        Self {
+            resolver: self.resolver.clone(),
+            static_: self.static_.clone(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn hook_rejects_large_files() {
+    let mut static_: Static<MemoryFs> = Static::from_memory_fs(memory_fs());
+    static_
+        .resolver
+        .set_on_resolved(|file| (file.size > 100).then_some(ResolveResult::PermissionDenied));
+
+    let res = serve(&static_, get("/data.bin").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let res = serve(&static_, get("/app.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+}
+
+#[tokio::test]
+async fn hook_sees_negotiated_content_type_and_priority() {
+    let seen = Arc::new(Mutex::new(None));
+    let mut static_: Static<MemoryFs> = Static::from_memory_fs(memory_fs());
+    static_
+        .resolver
+        .add_content_type_variants("/data.bin", [mime::TEXT_CSV, mime::APPLICATION_JSON]);
+    let recorder = seen.clone();
+    static_.resolver.set_on_resolved(move |file| {
+        *recorder.lock().unwrap() = Some((file.content_type.clone(), file.priority));
+        None
+    });
+
+    let req = get("/data.bin")
+        .header(header::ACCEPT, "application/json")
+        .header(PRIORITY, "u=1, i")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let (content_type, priority) = seen.lock().unwrap().take().expect("hook not called");
+    assert_eq!(
+        content_type.as_deref(),
+        Some("application/json; charset=utf-8")
+    );
+    assert_eq!(
+        priority,
+        Some(Priority {
+            urgency: 1,
+            incremental: true,
+        })
+    );
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::{memory_fs, serve};
+use http::{header, Method, Request, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::Static;
+
+fn options(path: &str) -> Request<()> {
+    Request::builder()
+        .method(Method::OPTIONS)
+        .uri(path)
+        .body(())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn accept_ranges_only_for_files() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let res = serve(&static_, options("/app.js")).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    assert_eq!(res.header(header::ACCEPT_RANGES), Some("bytes"));
+
+    // 没有index的目录返回目录列表，不支持范围请求
+    let mut fs = memory_fs();
+    fs.add("list/a.txt", Bytes::from_static(b"a"), None);
+    let mut static_ = Static::from_memory_fs(fs);
+    static_.autoindex(true);
+    let res = serve(&static_, options("/list/")).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    assert_eq!(res.header(header::ACCEPT_RANGES), None);
+    assert_eq!(res.header(header::ALLOW), Some("GET, HEAD, OPTIONS"));
+}
+
+#[tokio::test]
+async fn options_for_missing_resource() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let res = serve(&static_, options("/missing.js")).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[cfg(feature = "digest")]
+mod strong_etag {
+    use std::path::Path;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use hyper_staticfile::vfs::{FileOpener, MemoryFs};
+
+    use super::*;
+
+    /// 记录打开文件次数的`MemoryFs`
+    struct CountingFs {
+        inner: MemoryFs,
+        opens: Arc<AtomicUsize>,
+    }
+
+    impl FileOpener for CountingFs {
+        type File = <MemoryFs as FileOpener>::File;
+        type Future = <MemoryFs as FileOpener>::Future;
+
+        fn open(&self, path: &Path) -> Self::Future {
+            self.opens.fetch_add(1, Ordering::Relaxed);
+            self.inner.open(path)
+        }
+    }
+
+    #[tokio::test]
+    async fn options_skips_content_hashing() {
+        let opens = Arc::new(AtomicUsize::new(0));
+        let mut static_ = Static::with_opener(CountingFs {
+            inner: memory_fs(),
+            opens: opens.clone(),
+        });
+        static_.strong_etag(true);
+
+        let res = serve(&static_, options("/app.js")).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert_eq!(res.header(header::ETAG), None);
+        assert_eq!(opens.load(Ordering::Relaxed), 1);
+
+        // GET需要再打开一次文件计算强ETag
+        opens.store(0, Ordering::Relaxed);
+        let res = serve(&static_, common::get("/app.js").body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.header(header::ETAG).is_some());
+        assert_eq!(opens.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use common::{get, memory_fs, mtime, serve, APP_JS};
+use http::StatusCode;
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+fn static_() -> Static<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add("assets/x.js", Bytes::from_static(APP_JS), Some(mtime()));
+    let mut static_ = Static::from_memory_fs(fs);
+    // 带版本号的地址映射到不带版本号的资源，其它地址都不存在
+    static_
+        .resolver
+        .set_path_map(|path| path.strip_prefix("/v2").map(str::to_string));
+    static_
+}
+
+#[tokio::test]
+async fn versioned_prefix_maps_to_flat_path() {
+    let res = serve(&static_(), get("/v2/assets/x.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+}
+
+#[tokio::test]
+async fn unmapped_path_is_not_found() {
+    let res = serve(&static_(), get("/assets/x.js").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::{get, memory_fs, serve, APP_JS};
+use http::{header, StatusCode};
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+fn static_() -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.max_query_length(Some(16));
+    static_
+}
+
+#[tokio::test]
+async fn oversized_query_is_bad_request() {
+    let static_ = static_();
+    let uri = format!("/app.js?v={}", "x".repeat(100));
+    let res = serve(&static_, get(&uri).body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert!(res.body.is_empty());
+
+    // 重定向同样先检查查询字符串的长度
+    let uri = format!("/docs?v={}", "x".repeat(100));
+    let res = serve(&static_, get(&uri).body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(res.header(header::LOCATION), None);
+}
+
+#[tokio::test]
+async fn normal_query_passes() {
+    let static_ = static_();
+    let res = serve(&static_, get("/app.js?v=123").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+
+    let res = serve(&static_, get("/docs?v=123").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.header(header::LOCATION), Some("/docs/?v=123"));
+}
+
+#[tokio::test]
+async fn encoded_query_is_kept_in_redirect() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let res = serve(&static_, get("/docs?foo=bar%20baz").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.header(header::LOCATION), Some("/docs/?foo=bar%20baz"));
+}
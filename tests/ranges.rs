@@ -0,0 +1,176 @@
+mod common;
+
+use common::{data_bin, get, memory_fs, mtime, serve, TestResponse};
+use http::{header, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+async fn range(static_: &Static<MemoryFs>, value: &str) -> TestResponse {
+    let req = get("/data.bin")
+        .header(header::RANGE, value)
+        .body(())
+        .unwrap();
+    let res = serve(static_, req).await;
+    assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+    res
+}
+
+fn is_multipart(res: &TestResponse) -> bool {
+    res.header(header::CONTENT_TYPE)
+        .is_some_and(|v| v.starts_with("multipart/byteranges"))
+}
+
+/// `count`个互不相邻的单字节范围
+fn disjoint_ranges(count: usize) -> String {
+    let ranges: Vec<String> = (0..count).map(|i| format!("{0}-{0}", i * 10)).collect();
+    format!("bytes={}", ranges.join(","))
+}
+
+#[tokio::test]
+async fn overlapping_ranges_are_coalesced() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    for value in ["bytes=0-9,5-19", "bytes=10-19,0-9", "bytes=0-19,2-3"] {
+        let res = range(&static_, value).await;
+        assert!(!is_multipart(&res), "{value}");
+        assert_eq!(
+            res.header(header::CONTENT_RANGE),
+            Some("bytes 0-19/1024"),
+            "{value}"
+        );
+        assert_eq!(res.body, data_bin().slice(0..20), "{value}");
+    }
+}
+
+#[tokio::test]
+async fn disjoint_ranges_keep_request_order() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let res = range(&static_, "bytes=100-109,0-9").await;
+    assert!(is_multipart(&res));
+    let body = String::from_utf8_lossy(&res.body);
+    let first = body.find("Content-Range: bytes 100-109/1024").unwrap();
+    let second = body.find("Content-Range: bytes 0-9/1024").unwrap();
+    assert!(first < second);
+}
+
+#[tokio::test]
+async fn too_many_ranges_become_one() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    // 默认最多16个范围
+    let res = range(&static_, &disjoint_ranges(16)).await;
+    assert!(is_multipart(&res));
+
+    let res = range(&static_, &disjoint_ranges(17)).await;
+    assert!(!is_multipart(&res));
+    assert_eq!(res.header(header::CONTENT_RANGE), Some("bytes 0-160/1024"));
+    assert_eq!(res.body, data_bin().slice(0..161));
+}
+
+#[tokio::test]
+async fn configurable_range_limit() {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.max_ranges(Some(2));
+    let res = range(&static_, &disjoint_ranges(2)).await;
+    assert!(is_multipart(&res));
+
+    let res = range(&static_, &disjoint_ranges(3)).await;
+    assert!(!is_multipart(&res));
+    assert_eq!(res.header(header::CONTENT_RANGE), Some("bytes 0-20/1024"));
+    assert_eq!(res.body, data_bin().slice(0..21));
+}
+
+#[tokio::test]
+async fn weak_if_range_serves_full_body() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let res = serve(&static_, get("/data.bin").body(()).unwrap()).await;
+    let etag = res.header(header::ETAG).unwrap().to_string();
+    assert!(etag.starts_with("W/"));
+
+    // 与服务端的弱ETag完全相同，但If-Range只能使用强比较
+    let req = get("/data.bin")
+        .header(header::RANGE, "bytes=0-9")
+        .header(header::IF_RANGE, etag)
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_RANGE), None);
+    assert_eq!(res.body, data_bin());
+}
+
+#[tokio::test]
+async fn memory_fs_range_with_small_chunks() {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.chunk_size(Some(64));
+    let data = data_bin();
+    for (value, start, end) in [
+        ("bytes=100-299", 100, 299),
+        // 正好跨过块边界
+        ("bytes=63-64", 63, 64),
+        ("bytes=960-1023", 960, 1023),
+        ("bytes=1023-1023", 1023, 1023),
+        ("bytes=-1", 1023, 1023),
+    ] {
+        let res = range(&static_, value).await;
+        assert_eq!(
+            res.header(header::CONTENT_RANGE),
+            Some(format!("bytes {start}-{end}/1024").as_str()),
+            "{value}"
+        );
+        assert_eq!(res.body, data.slice(start..=end), "{value}");
+    }
+}
+
+#[tokio::test]
+async fn suffix_and_open_ended_ranges() {
+    let static_ = Static::from_memory_fs(memory_fs());
+    let data = data_bin();
+    for (value, start, end) in [
+        ("bytes=-500", 524, 1023),
+        ("bytes=500-", 500, 1023),
+        ("bytes=1023-", 1023, 1023),
+        // 与文件大小相同或更大的范围返回整个文件
+        ("bytes=0-1023", 0, 1023),
+        ("bytes=-1024", 0, 1023),
+        ("bytes=-2000", 0, 1023),
+        ("bytes=0-5000", 0, 1023),
+    ] {
+        let res = range(&static_, value).await;
+        assert_eq!(
+            res.header(header::CONTENT_RANGE),
+            Some(format!("bytes {start}-{end}/1024").as_str()),
+            "{value}"
+        );
+        assert_eq!(
+            res.header(header::CONTENT_LENGTH),
+            Some((end - start + 1).to_string().as_str()),
+            "{value}"
+        );
+        assert_eq!(res.body, data.slice(start..=end), "{value}");
+    }
+}
+
+#[tokio::test]
+async fn unsatisfiable_ranges() {
+    let mut fs = memory_fs();
+    fs.add("empty.bin", Bytes::new(), Some(mtime()));
+    let static_ = Static::from_memory_fs(fs);
+    for (path, value, size) in [
+        ("/data.bin", "bytes=1024-", 1024),
+        ("/data.bin", "bytes=99999-100000", 1024),
+        ("/empty.bin", "bytes=0-", 0),
+        ("/empty.bin", "bytes=-1", 0),
+    ] {
+        let req = get(path).header(header::RANGE, value).body(()).unwrap();
+        let res = serve(&static_, req).await;
+        assert_eq!(
+            res.status(),
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            "{path} {value}"
+        );
+        assert_eq!(
+            res.header(header::CONTENT_RANGE),
+            Some(format!("bytes */{size}").as_str()),
+            "{path} {value}"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! 不依赖`tokio::fs`的`FileOpener`，模拟只能按顺序读取的远程存储
+mod common;
+
+use std::{
+    collections::HashMap,
+    io::{Error as IoError, ErrorKind, SeekFrom},
+    path::Path,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use common::{data_bin, get, mtime, serve};
+use futures_util::{
+    future::{ready, Ready},
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use http::{header, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{
+    vfs::{FileAccess, FileOpener, FileWithMetadata, IntoFileAccess},
+    Static,
+};
+use tokio::io::AsyncSeek;
+
+/// 远程存储每次返回的数据块大小
+const CHUNK_SIZE: usize = 7;
+
+type RemoteBody = BoxStream<'static, Result<Bytes, IoError>>;
+
+struct RemoteObject {
+    body: RemoteBody,
+}
+
+impl IntoFileAccess for RemoteObject {
+    type Output = RemoteAccess;
+    fn into_file_access(self) -> Self::Output {
+        RemoteAccess {
+            body: self.body,
+            buffer: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+/// seek只修改位置，读取时按需从数据流中补齐缓存
+struct RemoteAccess {
+    body: RemoteBody,
+    buffer: Vec<u8>,
+    position: u64,
+}
+
+impl AsyncSeek for RemoteAccess {
+    fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> Result<(), IoError> {
+        self.position = match position {
+            SeekFrom::Start(offset) => offset,
+            _ => return Err(IoError::from(ErrorKind::Unsupported)),
+        };
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<u64, IoError>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+impl FileAccess for RemoteAccess {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        len: usize,
+    ) -> Poll<Result<Bytes, IoError>> {
+        let this = &mut *self;
+        let start = this.position as usize;
+        while this.buffer.len() <= start {
+            match this.body.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(chunk))) => this.buffer.extend_from_slice(&chunk),
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e)),
+                Poll::Ready(None) => return Poll::Ready(Ok(Bytes::new())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        let end = this.buffer.len().min(start + len);
+        this.position = end as u64;
+        Poll::Ready(Ok(Bytes::copy_from_slice(&this.buffer[start..end])))
+    }
+}
+
+struct RemoteOpener {
+    objects: Arc<HashMap<String, Bytes>>,
+}
+
+impl FileOpener for RemoteOpener {
+    type File = RemoteObject;
+    type Future = Ready<Result<FileWithMetadata<Self::File>, IoError>>;
+
+    fn open(&self, path: &Path) -> Self::Future {
+        let Some(data) = self.objects.get(&*path.to_string_lossy()).cloned() else {
+            return ready(Err(IoError::from(ErrorKind::NotFound)));
+        };
+        let size = data.len() as u64;
+        let chunks: Vec<_> = (0..data.len())
+            .step_by(CHUNK_SIZE)
+            .map(|start| Ok(data.slice(start..data.len().min(start + CHUNK_SIZE))))
+            .collect();
+        ready(Ok(FileWithMetadata {
+            handle: RemoteObject {
+                body: stream::iter(chunks).boxed(),
+            },
+            size,
+            modified: Some(mtime()),
+            is_dir: false,
+            unique_id: None,
+        }))
+    }
+}
+
+fn static_() -> Static<RemoteOpener> {
+    let objects = HashMap::from([("data.bin".to_string(), data_bin())]);
+    Static::with_opener(RemoteOpener {
+        objects: Arc::new(objects),
+    })
+}
+
+#[tokio::test]
+async fn remote_file_is_served() {
+    let res = serve(&static_(), get("/data.bin").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.header(header::CONTENT_LENGTH), Some("1024"));
+    assert_eq!(res.body, data_bin());
+
+    let res = serve(&static_(), get("/missing.bin").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn remote_file_serves_ranges() {
+    let req = get("/data.bin")
+        .header(header::RANGE, "bytes=100-199")
+        .body(())
+        .unwrap();
+    let res = serve(&static_(), req).await;
+    assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        res.header(header::CONTENT_RANGE),
+        Some("bytes 100-199/1024")
+    );
+    assert_eq!(res.body, data_bin().slice(100..200));
+
+    // 后一段在前一段之前，需要回到已经缓存的位置
+    let req = get("/data.bin")
+        .header(header::RANGE, "bytes=500-509,10-19")
+        .body(())
+        .unwrap();
+    let res = serve(&static_(), req).await;
+    assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+    let body = res.body.to_vec();
+    for range in [500..510, 10..20] {
+        let part = &data_bin()[range];
+        assert!(body.windows(part.len()).any(|window| window == part));
+    }
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{get, memory_fs, serve};
+use http::{HeaderName, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static};
+use mime_guess::mime;
+
+const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+fn static_(generate: bool) -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.request_id_header(Some(X_REQUEST_ID), generate);
+    static_
+}
+
+/// 是否为UUID v4格式
+fn is_uuid_v4(value: &str) -> bool {
+    let groups: Vec<_> = value.split('-').collect();
+    groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12])
+        && groups
+            .iter()
+            .all(|group| group.bytes().all(|b| b.is_ascii_hexdigit()))
+        && groups[2].starts_with('4')
+}
+
+#[tokio::test]
+async fn incoming_id_is_echoed() {
+    for path in ["/index.html", "/missing.html"] {
+        let req = get(path).header(X_REQUEST_ID, "abc-123").body(()).unwrap();
+        let res = serve(&static_(true), req).await;
+        assert_eq!(res.header(X_REQUEST_ID), Some("abc-123"), "{path}");
+    }
+}
+
+#[tokio::test]
+async fn missing_id_is_generated() {
+    let res = serve(&static_(true), get("/index.html").body(()).unwrap()).await;
+    let id = res.header(X_REQUEST_ID).unwrap();
+    assert!(is_uuid_v4(id), "{id}");
+
+    // 每个请求生成不同的ID
+    let other = serve(&static_(true), get("/index.html").body(()).unwrap()).await;
+    assert_ne!(other.header(X_REQUEST_ID), Some(id));
+
+    let res = serve(&static_(false), get("/index.html").body(()).unwrap()).await;
+    assert_eq!(res.header(X_REQUEST_ID), None);
+}
+
+#[tokio::test]
+async fn maintenance_response_echoes_id() {
+    let mut static_ = static_(true);
+    static_.maintenance(Some((
+        StatusCode::SERVICE_UNAVAILABLE,
+        Bytes::from_static(b"down"),
+        mime::TEXT_PLAIN,
+    )));
+    let req = get("/index.html")
+        .header(X_REQUEST_ID, "abc-123")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.header(X_REQUEST_ID), Some("abc-123"));
+
+    let res = serve(&static_, get("/index.html").body(()).unwrap()).await;
+    assert!(is_uuid_v4(res.header(X_REQUEST_ID).unwrap()));
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use std::path::Path;
+
+use common::{get, memory_fs, mtime, APP_JS};
+use http::{header, Method, Request};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, AcceptEncoding, Encoding, ResolveResult, Resolver};
+
+fn resolver() -> Resolver<MemoryFs> {
+    let mut fs = memory_fs();
+    fs.add("app.js.gz", Bytes::from_static(b"gzipped"), Some(mtime()));
+    let mut resolver = Resolver::with_opener(fs);
+    resolver.allowed_encodings = AcceptEncoding::all();
+    resolver
+}
+
+#[tokio::test]
+async fn resolve_request_results() {
+    let resolver = resolver();
+
+    let req = get("/app.js").body(()).unwrap();
+    let ResolveResult::Found(file) = resolver.resolve_request(&req).await.unwrap() else {
+        panic!("expected Found");
+    };
+    assert_eq!(file.path, Path::new("app.js"));
+    assert_eq!(file.size, APP_JS.len() as u64);
+    assert_eq!(file.encoding, None);
+
+    let req = get("/missing.js").body(()).unwrap();
+    let result = resolver.resolve_request(&req).await.unwrap();
+    assert!(matches!(result, ResolveResult::NotFound));
+
+    let req = get("/docs").body(()).unwrap();
+    let result = resolver.resolve_request(&req).await.unwrap();
+    assert!(matches!(
+        result,
+        ResolveResult::IsDirectory { ref redirect_to } if redirect_to == "/docs/"
+    ));
+
+    let req = Request::post("/app.js").body(()).unwrap();
+    let result = resolver.resolve_request(&req).await.unwrap();
+    assert!(matches!(result, ResolveResult::MethodNotMatched));
+}
+
+#[tokio::test]
+async fn resolve_metadata_of_found_file() {
+    let resolver = resolver();
+
+    let req = get("/app.js").body(()).unwrap();
+    let metadata = resolver.resolve_metadata(&req).await.unwrap().unwrap();
+    assert_eq!(metadata.path, Path::new("app.js"));
+    assert_eq!(metadata.logical_path, Path::new("app.js"));
+    assert_eq!(metadata.size, APP_JS.len() as u64);
+    assert_eq!(metadata.modified, Some(mtime()));
+    assert_eq!(
+        metadata.content_type.as_deref(),
+        Some("text/javascript; charset=utf-8")
+    );
+    assert_eq!(metadata.encoding, None);
+
+    // 命中预压缩文件时是实际输出的文件
+    let req = get("/app.js")
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .body(())
+        .unwrap();
+    let metadata = resolver.resolve_metadata(&req).await.unwrap().unwrap();
+    assert_eq!(metadata.path, Path::new("app.js.gz"));
+    assert_eq!(metadata.logical_path, Path::new("app.js"));
+    assert_eq!(metadata.size, 7);
+    assert_eq!(metadata.encoding, Some(Encoding::Gzip));
+}
+
+#[tokio::test]
+async fn resolve_metadata_without_file() {
+    let resolver = resolver();
+    for req in [
+        get("/missing.js").body(()).unwrap(),
+        get("/docs").body(()).unwrap(),
+        Request::builder()
+            .method(Method::DELETE)
+            .uri("/app.js")
+            .body(())
+            .unwrap(),
+    ] {
+        let path = req.uri().path().to_owned();
+        assert_eq!(
+            resolver.resolve_metadata(&req).await.unwrap(),
+            None,
+            "{path}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn deprecated_name_still_resolves() {
+    let resolver = resolver();
+    let req = get("/app.js").body(()).unwrap();
+    let result = resolver.resolve_request(&req).await.unwrap();
+    assert!(matches!(result, ResolveResult::Found(_)));
+
+    // 旧名称保留以兼容已有代码
+    #[allow(deprecated)]
+    let result = resolver.resovle_request(&req).await.unwrap();
+    assert!(matches!(result, ResolveResult::Found(_)));
+}
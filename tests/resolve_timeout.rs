@@ -0,0 +1,59 @@
+mod common;
+
+use std::path::Path;
+use std::time::Duration;
+
+use common::{get, memory_fs, serve};
+use futures_util::future::BoxFuture;
+use http::StatusCode;
+use hyper_staticfile::{
+    vfs::{FileOpener, FileWithMetadata, MemoryFs},
+    ResolveResult, Static,
+};
+
+/// 打开文件前先等待`delay`
+struct SlowFs {
+    inner: MemoryFs,
+    delay: Duration,
+}
+
+impl FileOpener for SlowFs {
+    type File = <MemoryFs as FileOpener>::File;
+    type Future = BoxFuture<'static, std::io::Result<FileWithMetadata<Self::File>>>;
+
+    fn open(&self, path: &Path) -> Self::Future {
+        let open = self.inner.open(path);
+        let delay = self.delay;
+        Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            open.await
+        })
+    }
+}
+
+fn static_(delay: Duration) -> Static<SlowFs> {
+    let mut static_ = Static::with_opener(SlowFs {
+        inner: memory_fs(),
+        delay,
+    });
+    static_.resolve_timeout(Some(Duration::from_millis(50)));
+    static_
+}
+
+#[tokio::test]
+async fn slow_opener_times_out() {
+    let static_ = static_(Duration::from_secs(5));
+    let req = get("/index.html").body(()).unwrap();
+    let result = static_.resolver.resolve_request(&req).await.unwrap();
+    assert!(matches!(result, ResolveResult::Unavailable));
+
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn fast_opener_within_timeout() {
+    let static_ = static_(Duration::ZERO);
+    let res = serve(&static_, get("/index.html").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
@@ -0,0 +1,44 @@
+use std::io::Cursor;
+
+use http::{header, Method, StatusCode};
+use http_body_util::BodyExt;
+use hyper::body::Bytes;
+use hyper_staticfile::{Body, ResolveResult, ResponseBuilder, DEFAULT_ALLOWED_METHODS};
+
+async fn build(result: ResolveResult<Cursor<Bytes>>) -> (http::response::Parts, Bytes) {
+    let res = ResponseBuilder::new().build(result).unwrap();
+    let (parts, body): (_, Body<Cursor<Bytes>>) = res.into_parts();
+    (parts, body.collect().await.unwrap().to_bytes())
+}
+
+#[tokio::test]
+async fn bad_request_is_400_with_empty_body() {
+    let (parts, body) = build(ResolveResult::BadRequest).await;
+    assert_eq!(parts.status, StatusCode::BAD_REQUEST);
+    assert!(parts.headers.get(header::ALLOW).is_none());
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn method_mismatch_is_405() {
+    let (parts, body) = build(ResolveResult::MethodNotMatched).await;
+    assert_eq!(parts.status, StatusCode::METHOD_NOT_ALLOWED);
+    assert!(parts.headers.contains_key(header::ALLOW));
+    assert!(body.is_empty());
+}
+
+#[test]
+fn empty_allowed_methods_fall_back_to_default() {
+    let default = DEFAULT_ALLOWED_METHODS
+        .iter()
+        .map(Method::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    assert_eq!(ResponseBuilder::new().allow_header(), default);
+    assert_eq!(
+        ResponseBuilder::new()
+            .allowed_methods(&[Method::GET])
+            .allow_header(),
+        "GET"
+    );
+}
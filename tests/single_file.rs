@@ -0,0 +1,60 @@
+mod common;
+
+use common::{get, serve, TempDir};
+use http::{header, StatusCode};
+use hyper_staticfile::Static;
+
+const HEALTH: &[u8] = b"{\"ok\":true}";
+
+#[tokio::test]
+async fn every_path_serves_the_file() {
+    let dir = TempDir::new("single-file");
+    dir.write("health.json", HEALTH);
+    dir.write("other.txt", b"other");
+    let static_ = Static::single_file(dir.path().join("health.json"));
+
+    for path in ["/", "/health.json", "/other.txt", "/a/b/c", "/.hidden"] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::OK, "{path}");
+        assert_eq!(
+            res.header(header::CONTENT_TYPE),
+            Some("application/json; charset=utf-8"),
+            "{path}"
+        );
+        assert_eq!(res.body, HEALTH, "{path}");
+    }
+}
+
+#[tokio::test]
+async fn conditional_request_uses_the_file() {
+    let dir = TempDir::new("single-file");
+    dir.write("health.json", HEALTH);
+    let static_ = Static::single_file(dir.path().join("health.json"));
+
+    let res = serve(&static_, get("/anything").body(()).unwrap()).await;
+    let etag = res.header(header::ETAG).unwrap().to_owned();
+    let req = get("/other")
+        .header(header::IF_NONE_MATCH, etag)
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+}
+
+#[tokio::test]
+async fn dotfile_name_is_served() {
+    let dir = TempDir::new("single-file");
+    dir.write(".well-known-file", b"dot");
+    let static_ = Static::single_file(dir.path().join(".well-known-file"));
+    let res = serve(&static_, get("/").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, &b"dot"[..]);
+}
+
+#[tokio::test]
+async fn missing_file_is_not_found() {
+    let dir = TempDir::new("single-file");
+    let static_ = Static::single_file(dir.path().join("missing.json"));
+    let res = serve(&static_, get("/").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
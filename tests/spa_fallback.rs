@@ -0,0 +1,83 @@
+mod common;
+
+use common::{get, memory_fs, serve, INDEX_HTML};
+use http::{header, Request, StatusCode};
+use hyper_staticfile::{vfs::MemoryFs, Static};
+
+fn static_(status: Option<StatusCode>) -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.spa_fallback("/index.html");
+    if let Some(status) = status {
+        static_.spa_fallback_status(status);
+    }
+    static_
+}
+
+#[tokio::test]
+async fn head_to_unknown_route_uses_fallback_headers() {
+    let static_ = static_(Some(StatusCode::NOT_FOUND));
+    let req = Request::head("/some/route").body(()).unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("text/html; charset=utf-8")
+    );
+    assert_eq!(
+        res.header(header::CONTENT_LENGTH),
+        Some(INDEX_HTML.len().to_string().as_str())
+    );
+    assert!(res.body.is_empty());
+}
+
+#[tokio::test]
+async fn get_to_unknown_route_returns_fallback_body() {
+    let static_ = static_(Some(StatusCode::NOT_FOUND));
+    let res = serve(&static_, get("/some/route").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(res.body, INDEX_HTML);
+
+    // 条件请求和范围请求不适用于替换了状态码的响应
+    let req = get("/some/route")
+        .header(header::IF_NONE_MATCH, "*")
+        .header(header::RANGE, "bytes=0-3")
+        .body(())
+        .unwrap();
+    let res = serve(&static_, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(res.body, INDEX_HTML);
+}
+
+#[tokio::test]
+async fn fallback_status_defaults_to_ok() {
+    let default = static_(None);
+    let req = Request::head("/some/route").body(()).unwrap();
+    let res = serve(&default, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.body.is_empty());
+
+    // 存在的文件不受回退状态码影响
+    let static_ = static_(Some(StatusCode::NOT_FOUND));
+    let res = serve(&static_, get("/index.html").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn deep_route_serves_fallback() {
+    let res = serve(&static_(None), get("/users/42/profile").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.header(header::CONTENT_TYPE),
+        Some("text/html; charset=utf-8")
+    );
+    assert_eq!(res.body, INDEX_HTML);
+}
+
+#[tokio::test]
+async fn missing_asset_is_not_found() {
+    let static_ = static_(None);
+    for path in ["/missing.js", "/users/42/style.css"] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND, "{path}");
+    }
+}
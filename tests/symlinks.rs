@@ -0,0 +1,59 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::os::unix::fs::symlink;
+
+use common::{get, serve, TempDir};
+use http::StatusCode;
+use hyper_staticfile::{vfs::TokioFileOpener, Static};
+
+/// `root`下有指向内部目录和外部目录的符号链接
+fn setup() -> TempDir {
+    let dir = TempDir::new("symlinks");
+    dir.write("root/inner/file.txt", b"inner");
+    dir.write("outside/secret.txt", b"secret");
+    symlink(
+        dir.path().join("root/inner"),
+        dir.path().join("root/link_in"),
+    )
+    .unwrap();
+    symlink(dir.path().join("outside"), dir.path().join("root/link_out")).unwrap();
+    dir
+}
+
+fn static_(dir: &TempDir, follow_symlinks: bool) -> Static<TokioFileOpener> {
+    let mut opener = TokioFileOpener::new(dir.path().join("root"));
+    opener.follow_symlinks(follow_symlinks);
+    Static::with_opener(opener)
+}
+
+#[tokio::test]
+async fn in_tree_symlink_is_served() {
+    let dir = setup();
+    let static_ = static_(&dir, false);
+    let res = serve(&static_, get("/link_in/file.txt").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, "inner");
+}
+
+#[tokio::test]
+async fn out_of_tree_symlink_is_denied() {
+    let dir = setup();
+    let res = serve(
+        &static_(&dir, false),
+        get("/link_out/secret.txt").body(()).unwrap(),
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    assert!(res.body.is_empty());
+
+    // 默认跟随所有符号链接
+    let res = serve(
+        &static_(&dir, true),
+        get("/link_out/secret.txt").body(()).unwrap(),
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, "secret");
+}
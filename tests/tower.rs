@@ -0,0 +1,70 @@
+#![cfg(feature = "tower")]
+
+mod common;
+
+use std::task::{Context, Poll};
+
+use common::{check_content_length, get, memory_fs, APP_JS};
+use futures_util::future::{poll_fn, BoxFuture, FutureExt};
+use http::{header, HeaderValue, Request, Response, StatusCode};
+use http_body_util::BodyExt;
+use hyper_staticfile::Static;
+use tower_service::Service;
+
+/// 最简单的中间件，给所有响应加上一个头部
+struct NoSniff<S>(S);
+
+impl<S, B, R> Service<Request<B>> for NoSniff<S>
+where
+    S: Service<Request<B>, Response = Response<R>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        self.0
+            .call(request)
+            .map(|result| {
+                result.map(|mut res| {
+                    res.headers_mut().insert(
+                        header::X_CONTENT_TYPE_OPTIONS,
+                        HeaderValue::from_static("nosniff"),
+                    );
+                    res
+                })
+            })
+            .boxed()
+    }
+}
+
+#[tokio::test]
+async fn static_works_inside_tower_middleware() {
+    let mut service = NoSniff(Static::from_memory_fs(memory_fs()));
+    poll_fn(|cx| Service::<Request<()>>::poll_ready(&mut service, cx))
+        .await
+        .unwrap();
+
+    let res = service
+        .call(get("/app.js").body(()).unwrap())
+        .await
+        .unwrap();
+    let (parts, body) = res.into_parts();
+    let body = body.collect().await.unwrap().to_bytes();
+    check_content_length(&parts, &body, false);
+    assert_eq!(parts.status, StatusCode::OK);
+    assert_eq!(parts.headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    assert_eq!(body, APP_JS);
+
+    let res = service
+        .call(Request::get("/missing.js").body(()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(res.headers()[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::{get, memory_fs, serve, APP_JS, INDEX_HTML};
+use http::StatusCode;
+use hyper_staticfile::{vfs::MemoryFs, Static, TrailingDots};
+
+fn static_(trailing_dots: TrailingDots) -> Static<MemoryFs> {
+    let mut static_ = Static::from_memory_fs(memory_fs());
+    static_.trailing_dots(trailing_dots);
+    static_
+}
+
+async fn status(static_: &Static<MemoryFs>, path: &str) -> StatusCode {
+    serve(static_, get(path).body(()).unwrap()).await.status()
+}
+
+#[tokio::test]
+async fn normalize_strips_trailing_dots_and_spaces() {
+    let static_ = static_(TrailingDots::Normalize);
+    for (path, body) in [
+        ("/app.js.", APP_JS),
+        ("/app.js%20", APP_JS),
+        ("/app.js.%20.", APP_JS),
+        ("/docs./index.html", INDEX_HTML),
+    ] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::OK, "{path}");
+        assert_eq!(res.body, body, "{path}");
+    }
+    let res = serve(&static_, get("/app.js.").body(()).unwrap()).await;
+    assert_eq!(
+        res.header(http::header::CONTENT_TYPE),
+        Some("text/javascript; charset=utf-8")
+    );
+}
+
+#[tokio::test]
+async fn reject_returns_bad_request() {
+    let static_ = static_(TrailingDots::Reject);
+    for path in ["/app.js.", "/app.js%20", "/docs./index.html", "/docs%20/"] {
+        assert_eq!(
+            status(&static_, path).await,
+            StatusCode::BAD_REQUEST,
+            "{path}"
+        );
+    }
+    assert_eq!(status(&static_, "/app.js").await, StatusCode::OK);
+    assert_eq!(status(&static_, "/docs/").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn keep_leaves_path_unchanged() {
+    let static_ = static_(TrailingDots::Keep);
+    assert_eq!(status(&static_, "/app.js.").await, StatusCode::NOT_FOUND);
+    assert_eq!(status(&static_, "/app.js%20").await, StatusCode::NOT_FOUND);
+    assert_eq!(status(&static_, "/app.js").await, StatusCode::OK);
+}
+
+#[cfg(windows)]
+#[test]
+fn default_normalizes_on_windows() {
+    assert_eq!(TrailingDots::default(), TrailingDots::Normalize);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn default_keeps_elsewhere() {
+    assert_eq!(TrailingDots::default(), TrailingDots::Keep);
+}
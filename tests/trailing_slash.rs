@@ -0,0 +1,124 @@
+mod common;
+
+use common::{get, memory_fs, mtime, serve, TestResponse, APP_JS, INDEX_HTML};
+use http::{header, StatusCode};
+use hyper::body::Bytes;
+use hyper_staticfile::{vfs::MemoryFs, Static, TrailingSlash};
+
+fn static_() -> Static<MemoryFs> {
+    Static::from_memory_fs(memory_fs())
+}
+
+async fn with_policy(policy: TrailingSlash, path: &str) -> TestResponse {
+    let mut static_ = static_();
+    static_.trailing_slash(policy);
+    serve(&static_, get(path).body(()).unwrap()).await
+}
+
+#[tokio::test]
+async fn add_to_directories_policy() {
+    let res = with_policy(TrailingSlash::AddToDirectories, "/docs").await;
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.header(header::LOCATION), Some("/docs/"));
+    let res = with_policy(TrailingSlash::AddToDirectories, "/docs/").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, INDEX_HTML);
+
+    let res = with_policy(TrailingSlash::AddToDirectories, "/app.js").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+    let res = with_policy(TrailingSlash::AddToDirectories, "/app.js/").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn no_redirect_policy() {
+    for path in ["/docs", "/docs/"] {
+        let res = with_policy(TrailingSlash::NoRedirect, path).await;
+        assert_eq!(res.status(), StatusCode::OK, "{path}");
+        assert_eq!(res.header(header::LOCATION), None, "{path}");
+        assert_eq!(res.body, INDEX_HTML, "{path}");
+    }
+
+    // 文件忽略末尾斜杠
+    for path in ["/app.js", "/app.js/"] {
+        let res = with_policy(TrailingSlash::NoRedirect, path).await;
+        assert_eq!(res.status(), StatusCode::OK, "{path}");
+        assert_eq!(res.header(header::LOCATION), None, "{path}");
+        assert_eq!(res.body, APP_JS, "{path}");
+    }
+}
+
+#[tokio::test]
+async fn remove_from_files_policy() {
+    let res = with_policy(TrailingSlash::RemoveFromFiles, "/docs").await;
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.header(header::LOCATION), Some("/docs/"));
+    let res = with_policy(TrailingSlash::RemoveFromFiles, "/docs/").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, INDEX_HTML);
+
+    let res = with_policy(TrailingSlash::RemoveFromFiles, "/app.js").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body, APP_JS);
+    let res = with_policy(TrailingSlash::RemoveFromFiles, "/app.js/").await;
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.header(header::LOCATION), Some("/app.js"));
+}
+
+#[tokio::test]
+async fn directory_without_normalization_serves_index() {
+    let mut static_ = static_();
+    static_.normalize_trailing_slash(false);
+    for path in ["/docs", "/docs/"] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::OK, "{path}");
+        assert_eq!(res.header(header::LOCATION), None, "{path}");
+        assert_eq!(res.body, INDEX_HTML, "{path}");
+    }
+}
+
+#[tokio::test]
+async fn directory_with_normalization_redirects() {
+    let res = serve(&static_(), get("/docs").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.header(header::LOCATION), Some("/docs/"));
+}
+
+#[tokio::test]
+async fn redirect_encodes_directory_names() {
+    let mut fs = memory_fs();
+    for dir in ["my docs", "a#b", "文档"] {
+        fs.add(
+            format!("{dir}/index.html"),
+            Bytes::from_static(INDEX_HTML),
+            Some(mtime()),
+        );
+    }
+    let static_ = Static::from_memory_fs(fs);
+    for (path, location) in [
+        ("/my%20docs", "/my%20docs/"),
+        ("/a%23b", "/a%23b/"),
+        ("/%E6%96%87%E6%A1%A3", "/%E6%96%87%E6%A1%A3/"),
+    ] {
+        let res = serve(&static_, get(path).body(()).unwrap()).await;
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY, "{path}");
+        assert_eq!(res.header(header::LOCATION), Some(location), "{path}");
+    }
+}
+
+#[tokio::test]
+async fn index_without_slash_has_content_location() {
+    let mut static_ = static_();
+    static_.normalize_trailing_slash(false);
+    let res = serve(&static_, get("/docs").body(()).unwrap()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.header(header::CONTENT_LOCATION),
+        Some("/docs/index.html")
+    );
+
+    // 带末尾斜杠时相对链接不受影响，不需要
+    let res = serve(&static_, get("/docs/").body(()).unwrap()).await;
+    assert_eq!(res.header(header::CONTENT_LOCATION), None);
+}
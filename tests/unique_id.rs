@@ -0,0 +1,34 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use common::{memory_fs, TempDir};
+use hyper_staticfile::vfs::{FileOpener, TokioFileOpener};
+
+async fn unique_id<O: FileOpener>(opener: &O, path: &str) -> Option<u64> {
+    opener.open(Path::new(path)).await.unwrap().unique_id
+}
+
+#[tokio::test]
+async fn tokio_opener_sets_unique_id() {
+    let dir = TempDir::new("unique-id");
+    dir.write("a.txt", b"same");
+    dir.write("b.txt", b"same");
+    std::fs::hard_link(dir.path().join("a.txt"), dir.path().join("link.txt")).unwrap();
+    let opener = TokioFileOpener::new(dir.path());
+
+    let a = unique_id(&opener, "a.txt").await;
+    let metadata = std::fs::metadata(dir.path().join("a.txt")).unwrap();
+    assert_eq!(a, Some(metadata.dev().rotate_left(32) ^ metadata.ino()));
+    // 硬链接是同一个文件，内容相同的另一个文件不是
+    assert_eq!(unique_id(&opener, "link.txt").await, a);
+    assert_ne!(unique_id(&opener, "b.txt").await, a);
+}
+
+#[tokio::test]
+async fn memory_fs_has_no_unique_id() {
+    assert_eq!(unique_id(&memory_fs(), "app.js").await, None);
+}